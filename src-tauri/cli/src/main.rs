@@ -0,0 +1,170 @@
+//! Runs the same automations as the tray app without a window, logging to stdout.
+//!
+//! ```text
+//! reveal-cli [--config <reveal.toml|reveal.json>] [--data <dir>] [--open]
+//! ```
+//!
+//! Multi search links only open in the browser with `--open` or `autoOpen` in the config.
+
+use anyhow::{anyhow, Context};
+use reveal_core::{
+    config::ConfigFile, connection::ConnectionSupervisor, lcu::ConnectionSource, manage_state,
+    match_db::MatchDb, Config, Headless,
+};
+use std::path::{Path, PathBuf};
+
+const USAGE: &str =
+    "Usage: reveal-cli [--config <reveal.toml|reveal.json>] [--data <dir>] [--open]";
+
+struct Args {
+    config: Option<PathBuf>,
+    data: PathBuf,
+    /// Opens the multi search link in the browser once champ select starts.
+    open: bool,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Self> {
+        let mut parsed = Args {
+            config: None,
+            data: PathBuf::from("reveal-data"),
+            open: false,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+            match arg.as_str() {
+                "--config" => parsed.config = Some(value()?.into()),
+                "--data" => parsed.data = value()?.into(),
+                "--open" => parsed.open = true,
+                _ => return Err(anyhow!("unknown argument {}", arg)),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Reads a single config, TOML or JSON by extension, with the same fields as the app's.
+fn load_config(path: &Path) -> anyhow::Result<Config> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    // Through a JSON value so numeric keys like the queue ids in `acceptRules` parse from TOML.
+    let value: serde_json::Value = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&text)?,
+        _ => serde_json::from_str(&text)?,
+    };
+
+    Ok(serde_json::from_value(value)?)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    if std::env::args().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let args = Args::parse(std::env::args().skip(1)).context(USAGE)?;
+
+    let mut config = match &args.config {
+        Some(path) => load_config(path)?,
+        None => {
+            println!("No config given, using the defaults");
+            // Nobody may be at the machine to see a browser window.
+            Config {
+                auto_open: false,
+                ..Config::default()
+            }
+        }
+    };
+    config.auto_open |= args.open;
+    println!(
+        "Auto accept: {}, auto open: {}, multi search: {}",
+        config.auto_accept, config.auto_open, config.multi_provider
+    );
+
+    std::fs::create_dir_all(&args.data)
+        .with_context(|| format!("failed to create {}", args.data.display()))?;
+
+    // Without a path the profiles are never written back, the config file stays as written.
+    let mut profiles = ConfigFile::default();
+    profiles.set_active_config(config);
+    let match_db = MatchDb::open(&args.data.join("matches.db"))?;
+
+    let mut builder = Headless::builder().log_dir(args.data.join("logs"));
+    manage_state(&mut builder, profiles, match_db);
+    let host = builder.build();
+
+    let mut events = host.subscribe();
+    tokio::spawn(async move {
+        while let Ok(sent) = events.recv().await {
+            println!("Event {}: {}", sent.event, sent.payload);
+        }
+    });
+
+    let supervisor = ConnectionSupervisor::new(host, ConnectionSource::from_env());
+    tokio::select! {
+        _ = supervisor.run() => {}
+        _ = tokio::signal::ctrl_c() => println!("Shutting down"),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reveal_core::ready_check::ReadyCheckAction;
+
+    fn args(args: &[&str]) -> anyhow::Result<Args> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_arguments() {
+        let parsed = args(&["--config", "reveal.toml", "--data", "/tmp/reveal"]).unwrap();
+        assert_eq!(parsed.config, Some(PathBuf::from("reveal.toml")));
+        assert_eq!(parsed.data, PathBuf::from("/tmp/reveal"));
+        assert!(!parsed.open);
+        assert!(args(&["--open"]).unwrap().open);
+
+        assert!(args(&["--config"]).is_err());
+        assert!(args(&["--verbose"]).is_err());
+    }
+
+    #[test]
+    fn reads_toml_config_with_queue_rules() {
+        // Unique per run so parallel runs of the suite don't read each other's file.
+        let path =
+            std::env::temp_dir().join(format!("reveal-cli-test-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+autoOpen = false
+autoAccept = true
+acceptDelay = 1500
+
+[acceptRules]
+defaultAction = "notify"
+
+[acceptRules.queues]
+420 = "accept"
+450 = "decline"
+"#,
+        )
+        .unwrap();
+
+        let config = load_config(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!config.auto_open);
+        assert_eq!(config.accept_delay, 1500);
+        assert_eq!(config.accept_rules.default_action, ReadyCheckAction::Notify);
+        assert_eq!(
+            config.accept_rules.queues.get(&450),
+            Some(&ReadyCheckAction::Decline)
+        );
+    }
+}
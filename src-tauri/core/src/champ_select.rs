@@ -0,0 +1,771 @@
+use crate::{
+    dodge_history::{fetch_queue_id, now_ms},
+    lcu::LcuClient,
+    lobby::{self, Lobby, Participant},
+    match_db::{query_db, with_db, PlayedWith},
+    notifications::{notify, NotifyEvent},
+    region::{get_region, Region},
+    team_export::copy_team_text,
+    utils::{display_champ_select, lenient, lenient_elements, lenient_vec},
+    AppConfig, Host, ManagedAutoPickState, ManagedTeamTracker,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChampSelectSession {
+    pub allow_battle_boost: bool,
+    pub allow_duplicate_picks: bool,
+    pub allow_locked_events: bool,
+    pub allow_rerolling: bool,
+    pub allow_skin_selection: bool,
+    pub bench_enabled: bool,
+    pub boostable_skin_count: i64,
+    pub counter: i64,
+    pub game_id: u64,
+    pub has_simultaneous_bans: bool,
+    pub has_simultaneous_picks: bool,
+    pub is_custom_game: bool,
+    pub is_spectating: bool,
+    pub local_player_cell_id: i64,
+    pub locked_event_index: i64,
+    pub recovery_counter: i64,
+    pub rerolls_remaining: i64,
+    pub skip_champion_select: bool,
+    #[serde(deserialize_with = "lenient")]
+    pub timer: Timer,
+    #[serde(deserialize_with = "lenient_vec")]
+    pub my_team: Vec<ChampSelectPlayer>,
+    #[serde(deserialize_with = "lenient_vec")]
+    pub their_team: Vec<ChampSelectPlayer>,
+    /// Grouped by turn, every inner list is one ban or pick phase step.
+    #[serde(deserialize_with = "lenient_turns")]
+    pub actions: Vec<Vec<ChampSelectAction>>,
+    #[serde(deserialize_with = "lenient")]
+    pub bans: Bans,
+    #[serde(deserialize_with = "lenient_vec")]
+    pub trades: Vec<SwapRequest>,
+    #[serde(deserialize_with = "lenient_vec")]
+    pub pick_order_swaps: Vec<SwapRequest>,
+    #[serde(deserialize_with = "lenient_vec")]
+    pub position_swaps: Vec<SwapRequest>,
+    #[serde(deserialize_with = "lenient_vec")]
+    pub bench_champions: Vec<BenchChampion>,
+}
+
+/// [`lenient_vec`] one level down, a bad action only drops itself rather than its whole turn.
+fn lenient_turns<'de, D>(deserializer: D) -> Result<Vec<Vec<ChampSelectAction>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let turns: Vec<Value> = lenient_vec(deserializer)?;
+    Ok(turns.into_iter().map(lenient_elements).collect())
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Timer {
+    pub adjusted_time_left_in_phase: u64,
+    pub internal_now_in_epoch_ms: u64,
+    pub is_infinite: bool,
+    pub phase: String,
+    pub total_time_in_phase: i64,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChampSelectPlayer {
+    pub cell_id: i64,
+    pub team: i64,
+    pub champion_id: i64,
+    pub champion_pick_intent: i64,
+    pub selected_skin_id: i64,
+    pub assigned_position: String,
+    pub spell1_id: u64,
+    pub spell2_id: u64,
+    pub summoner_id: u64,
+    pub puuid: String,
+    pub game_name: String,
+    pub tag_line: String,
+    pub name_visibility_type: String,
+    pub obfuscated_puuid: String,
+    pub obfuscated_summoner_id: u64,
+    pub entitled_feature_type: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionType {
+    Ban,
+    Pick,
+    TenBansReveal,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChampSelectAction {
+    pub id: i64,
+    pub actor_cell_id: i64,
+    pub champion_id: i64,
+    pub completed: bool,
+    pub is_ally_action: bool,
+    pub is_in_progress: bool,
+    pub pick_turn: i64,
+    #[serde(rename = "type", deserialize_with = "lenient")]
+    pub action_type: ActionType,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Bans {
+    pub my_team_bans: Vec<i64>,
+    pub their_team_bans: Vec<i64>,
+    pub num_bans: i64,
+}
+
+/// Used for champion trades, pick order swaps and position swaps alike.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SwapRequest {
+    pub id: i64,
+    pub cell_id: i64,
+    pub state: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BenchChampion {
+    pub champion_id: i64,
+    pub is_priority: bool,
+}
+
+impl ChampSelectSession {
+    pub fn local_player(&self) -> Option<&ChampSelectPlayer> {
+        self.my_team
+            .iter()
+            .find(|p| p.cell_id == self.local_player_cell_id)
+    }
+
+    /// The ban or pick the local player has to make right now.
+    pub fn local_action_in_progress(&self) -> Option<&ChampSelectAction> {
+        self.actions.iter().flatten().find(|a| {
+            a.actor_cell_id == self.local_player_cell_id && a.is_in_progress && !a.completed
+        })
+    }
+
+    /// Bans from both teams, including completed ban actions not yet listed in `bans`.
+    pub fn banned_champions(&self) -> HashSet<i64> {
+        let completed_bans = self
+            .actions
+            .iter()
+            .flatten()
+            .filter(|a| a.action_type == ActionType::Ban && a.completed)
+            .map(|a| a.champion_id);
+
+        self.bans
+            .my_team_bans
+            .iter()
+            .chain(self.bans.their_team_bans.iter())
+            .copied()
+            .chain(completed_bans)
+            .filter(|id| *id > 0)
+            .collect()
+    }
+}
+
+/// Champion priority lists for automatic picks and bans.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AutoPickConfig {
+    pub pick_enabled: bool,
+    pub ban_enabled: bool,
+    /// Lock the champion in, otherwise only hover it.
+    pub lock_in: bool,
+    /// Champion ids by assigned position (`top`, `jungle`, `middle`, `bottom`, `utility`),
+    /// tried in order. The `any` list follows every role and covers blind queues.
+    pub picks: HashMap<String, Vec<i64>>,
+    pub bans: HashMap<String, Vec<i64>>,
+}
+
+impl AutoPickConfig {
+    fn priorities(list: &HashMap<String, Vec<i64>>, position: &str) -> Vec<i64> {
+        let role = list.get(position).into_iter().flatten();
+        let any = list.get("any").into_iter().flatten();
+        role.chain(any).copied().collect()
+    }
+}
+
+/// Remembers which actions were already handled so repeated session updates don't re-send them.
+#[derive(Debug, Default)]
+pub struct AutoPickState {
+    game_id: u64,
+    handled_actions: HashSet<i64>,
+    /// The last pick we sent a notification for.
+    announced_turn: Option<i64>,
+}
+
+impl AutoPickState {
+    fn start_game(&mut self, game_id: u64) {
+        if self.game_id != game_id {
+            self.game_id = game_id;
+            self.handled_actions.clear();
+            self.announced_turn = None;
+        }
+    }
+
+    fn is_handled(&mut self, game_id: u64, action_id: i64) -> bool {
+        self.start_game(game_id);
+        self.handled_actions.contains(&action_id)
+    }
+
+    /// True only the first time this pick is seen.
+    fn is_new_turn(&mut self, game_id: u64, action_id: i64) -> bool {
+        self.start_game(game_id);
+        self.announced_turn.replace(action_id) != Some(action_id)
+    }
+}
+
+/// Picks the first champion in `priorities` that is `available` and not taken.
+///
+/// Picks skip bans and champions already picked by anyone, bans skip champions a teammate
+/// picked or declared as their pick intent. Without the local team there's no telling what
+/// teammates want, so nothing gets banned.
+pub fn choose_champion(
+    session: &ChampSelectSession,
+    action_type: &ActionType,
+    priorities: &[i64],
+    available: &HashSet<i64>,
+) -> Option<i64> {
+    if *action_type == ActionType::Ban && session.local_player().is_none() {
+        return None;
+    }
+
+    let banned = session.banned_champions();
+    let teammates = session
+        .my_team
+        .iter()
+        .filter(|p| p.cell_id != session.local_player_cell_id);
+
+    let taken: HashSet<i64> = match action_type {
+        ActionType::Ban => teammates
+            .flat_map(|p| [p.champion_id, p.champion_pick_intent])
+            .collect(),
+        _ => {
+            let enemies = session
+                .their_team
+                .iter()
+                .filter(|_| !session.allow_duplicate_picks);
+            teammates.chain(enemies).map(|p| p.champion_id).collect()
+        }
+    };
+
+    priorities
+        .iter()
+        .copied()
+        .find(|id| *id > 0 && available.contains(id) && !banned.contains(id) && !taken.contains(id))
+}
+
+/// Hovers, and optionally locks, a champion for the local player's current pick or ban.
+pub async fn handle_champ_select_actions<H: Host>(
+    session: &ChampSelectSession,
+    host: &H,
+    remoting_client: &LcuClient,
+) {
+    let Some(action) = session.local_action_in_progress() else {
+        return;
+    };
+
+    let cfg = {
+        let cfg = host.state::<AppConfig>();
+        let cfg = cfg.0.lock().await;
+        cfg.auto_pick.clone()
+    };
+
+    let (enabled, list, available_endpoint) = match action.action_type {
+        ActionType::Pick => (cfg.pick_enabled, &cfg.picks, "pickable-champion-ids"),
+        ActionType::Ban => (cfg.ban_enabled, &cfg.bans, "bannable-champion-ids"),
+        _ => return,
+    };
+
+    if !enabled {
+        return;
+    }
+
+    let auto_pick = host.state::<ManagedAutoPickState>();
+    if auto_pick
+        .0
+        .lock()
+        .await
+        .is_handled(session.game_id, action.id)
+    {
+        return;
+    }
+
+    let position = session
+        .local_player()
+        .map(|p| p.assigned_position.as_str())
+        .unwrap_or_default();
+    let priorities = AutoPickConfig::priorities(list, position);
+
+    let available = remoting_client
+        .get(format!("/lol-champ-select/v1/{}", available_endpoint))
+        .await
+        .ok()
+        .and_then(|ids| serde_json::from_value::<HashSet<i64>>(ids).ok());
+
+    let Some(available) = available else {
+        println!("Failed to fetch {}", available_endpoint);
+        return;
+    };
+
+    let Some(champion_id) = choose_champion(session, &action.action_type, &priorities, &available)
+    else {
+        println!(
+            "No champion left in the {:?} priority list",
+            action.action_type
+        );
+        return;
+    };
+
+    println!("Auto {:?}: champion {}", action.action_type, champion_id);
+
+    let resp = remoting_client
+        .patch(
+            format!("/lol-champ-select/v1/session/actions/{}", action.id),
+            serde_json::json!({
+                "championId": champion_id,
+                "completed": cfg.lock_in,
+            }),
+        )
+        .await;
+
+    if resp.is_ok() {
+        auto_pick.0.lock().await.handled_actions.insert(action.id);
+    }
+}
+
+/// Notifies the user once when their pick comes up.
+pub async fn notify_pick_turn<H: Host>(session: &ChampSelectSession, host: &H) {
+    let Some(action) = session.local_action_in_progress() else {
+        return;
+    };
+
+    if action.action_type != ActionType::Pick {
+        return;
+    }
+
+    let is_new = {
+        let auto_pick = host.state::<ManagedAutoPickState>();
+        let mut auto_pick = auto_pick.0.lock().await;
+        auto_pick.is_new_turn(session.game_id, action.id)
+    };
+
+    if is_new {
+        notify(
+            host,
+            NotifyEvent::PickTurn,
+            "Your turn to pick",
+            "Lock in a champion before the timer runs out",
+        )
+        .await;
+    }
+}
+
+/// Tracks the chat participants of the current champ select, fed by `/chat/v5/participants`
+/// events so the team is emitted as soon as someone joins.
+#[derive(Debug, Default)]
+pub struct TeamTracker {
+    active: bool,
+    last_count: usize,
+    opened: bool,
+    region: Option<Region>,
+    /// Known from the first session update, the chat usually fills up before that.
+    game_id: Option<u64>,
+    /// Our champion as last written to the match database.
+    recorded_pick: i64,
+    team: Vec<Participant>,
+}
+
+/// What a champ select session update adds to the match database.
+#[derive(Debug)]
+enum SessionChange {
+    Unchanged,
+    Pick,
+    /// The first update of a game, with the teammates who joined the chat before it.
+    Game(Option<Vec<Participant>>),
+}
+
+impl TeamTracker {
+    fn session_change(&mut self, game_id: u64, pick: i64) -> SessionChange {
+        if self.game_id == Some(game_id) {
+            if self.recorded_pick == pick {
+                return SessionChange::Unchanged;
+            }
+            self.recorded_pick = pick;
+            return SessionChange::Pick;
+        }
+
+        self.game_id = Some(game_id);
+        self.recorded_pick = pick;
+        // An ended champ select's team belongs to another game.
+        SessionChange::Game(self.active.then(|| self.team.clone()))
+    }
+}
+
+/// A teammate with how we fared together before, `None` if we never met.
+#[derive(Debug, Clone, Serialize)]
+pub struct KnownParticipant {
+    #[serde(flatten)]
+    pub participant: Participant,
+    pub played_with: Option<PlayedWith>,
+}
+
+/// The payload of `champ_select_started`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChampSelectTeam {
+    pub participants: Vec<KnownParticipant>,
+}
+
+impl ChampSelectTeam {
+    pub fn new(team: &Lobby, mut history: HashMap<String, PlayedWith>) -> Self {
+        Self {
+            participants: team
+                .participants
+                .iter()
+                .map(|p| KnownParticipant {
+                    participant: p.clone(),
+                    played_with: history.remove(&p.puuid),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Starts tracking a new champ select and handles whoever already joined.
+pub async fn handle_champ_select_start<H: Host>(app_client: &LcuClient, host: &H) {
+    let region = match get_region(app_client).await {
+        Ok(region) => Some(region),
+        Err(err) => {
+            println!("Failed to read region: {}", err);
+            None
+        }
+    };
+
+    {
+        let tracker = host.state::<ManagedTeamTracker>();
+        *tracker.0.lock().await = TeamTracker {
+            active: true,
+            region,
+            ..Default::default()
+        };
+    }
+
+    match lobby::get_lobby_info(app_client).await {
+        Ok(team) => handle_team_update(team, host).await,
+        Err(err) => println!("Failed to read champ select participants: {}", err),
+    }
+}
+
+pub async fn handle_champ_select_end<H: Host>(host: &H) {
+    let tracker = host.state::<ManagedTeamTracker>();
+    tracker.0.lock().await.active = false;
+}
+
+/// Records the game and our pick in the match database, along with the teammates seen so far
+/// on the first update. Later updates are only written when our pick changed.
+pub async fn record_champ_select_session<H: Host>(
+    session: &ChampSelectSession,
+    host: &H,
+    remoting_client: &LcuClient,
+) {
+    let pick = session
+        .local_player()
+        .map(|p| p.champion_id)
+        .unwrap_or_default();
+
+    let change = {
+        let tracker = host.state::<ManagedTeamTracker>();
+        let mut tracker = tracker.0.lock().await;
+        tracker.session_change(session.game_id, pick)
+    };
+
+    // The queue is only looked up once per game.
+    let (queue_id, new_team) = match change {
+        SessionChange::Unchanged => return,
+        SessionChange::Pick => (None, None),
+        SessionChange::Game(team) => (fetch_queue_id(remoting_client).await, team),
+    };
+
+    let session = session.clone();
+    with_db(host, "champ select", move |db| {
+        db.record_champ_select(&session, queue_id, now_ms())?;
+        match new_team {
+            Some(team) => db.record_teammates(session.game_id, &team),
+            None => Ok(()),
+        }
+    })
+    .await;
+}
+
+/// Emits `champ_select_started`, with our history with each teammate, whenever the team grows
+/// and opens the multi search once.
+pub async fn handle_team_update<H: Host>(team: Lobby, host: &H) {
+    let cfg = {
+        let cfg = host.state::<AppConfig>();
+        let cfg = cfg.0.lock().await;
+        cfg.clone()
+    };
+
+    // Not held across the lookups below, the session and chat handlers need it meanwhile.
+    let (game_id, region, open) = {
+        let tracker = host.state::<ManagedTeamTracker>();
+        let mut tracker = tracker.0.lock().await;
+
+        let count = team.participants.len();
+        if !tracker.active || count <= tracker.last_count {
+            return;
+        }
+
+        tracker.last_count = count;
+        tracker.team = team.participants.clone();
+
+        let open = cfg.auto_open && tracker.region.is_some() && !tracker.opened;
+        tracker.opened |= open;
+        (tracker.game_id, tracker.region.clone(), open)
+    };
+
+    let participants = team.participants.clone();
+    let history = query_db(host, move |db| db.played_with(&participants, game_id)).await;
+    let history = history.unwrap_or_else(|err| {
+        println!("Failed to look up teammate history: {}", err);
+        HashMap::new()
+    });
+    host.emit("champ_select_started", ChampSelectTeam::new(&team, history));
+
+    if let Some(game_id) = game_id {
+        let participants = team.participants.clone();
+        with_db(host, "teammates", move |db| {
+            db.record_teammates(game_id, &participants)
+        })
+        .await;
+    }
+
+    let Some(region) = region else {
+        return;
+    };
+
+    if open {
+        if let Err(err) = display_champ_select(&team, &region, &cfg) {
+            println!("Failed to open multi search: {}", err);
+        }
+    }
+
+    // Copied again as teammates join so the clipboard always has the full team.
+    if cfg.auto_copy {
+        if let Err(err) = copy_team_text(host, &team, &region, &cfg, cfg.team_format) {
+            println!("Failed to copy team: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_full_session() {
+        let session: ChampSelectSession = serde_json::from_value(json!({
+            "gameId": 42,
+            "localPlayerCellId": 1,
+            "myTeam": [
+                { "cellId": 0, "championId": 0, "championPickIntent": 157, "assignedPosition": "middle" },
+                { "cellId": 1, "championId": 64, "assignedPosition": "jungle", "puuid": "me" }
+            ],
+            "theirTeam": [{ "cellId": 5, "championId": 0 }],
+            "actions": [[
+                { "id": 1, "actorCellId": 1, "championId": 0, "completed": false,
+                  "isAllyAction": true, "isInProgress": true, "type": "ban" }
+            ]],
+            "bans": { "myTeamBans": [238], "theirTeamBans": [], "numBans": 10 },
+            "benchChampions": [{ "championId": 1, "isPriority": false }],
+            "timer": { "adjustedTimeLeftInPhase": 25000, "phase": "BAN_PICK" }
+        }))
+        .unwrap();
+
+        assert_eq!(session.game_id, 42);
+        assert_eq!(session.my_team[0].champion_pick_intent, 157);
+        assert_eq!(session.actions[0][0].action_type, ActionType::Ban);
+        assert_eq!(session.bans.my_team_bans, vec![238]);
+        assert_eq!(session.timer.phase, "BAN_PICK");
+    }
+
+    fn draft_session() -> ChampSelectSession {
+        serde_json::from_value(json!({
+            "gameId": 42,
+            "localPlayerCellId": 0,
+            "myTeam": [
+                { "cellId": 0, "assignedPosition": "middle" },
+                { "cellId": 1, "championId": 64 },
+                { "cellId": 2, "championPickIntent": 157 }
+            ],
+            "theirTeam": [{ "cellId": 5, "championId": 103 }],
+            "actions": [
+                [{ "id": 1, "actorCellId": 5, "championId": 238, "completed": true, "type": "ban" }],
+                [{ "id": 2, "actorCellId": 0, "isInProgress": true, "type": "pick" }]
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn picks_skip_banned_taken_and_unowned_champions() {
+        let session = draft_session();
+        let available = HashSet::from([238, 64, 103, 157, 7]);
+
+        // 238 banned, 64 picked by a teammate, 103 picked by an enemy, 99 not owned
+        let pick = choose_champion(
+            &session,
+            &ActionType::Pick,
+            &[238, 64, 103, 99, 7],
+            &available,
+        );
+        assert_eq!(pick, Some(7));
+        assert_eq!(session.local_action_in_progress().unwrap().id, 2);
+    }
+
+    #[test]
+    fn bans_never_hit_teammate_intent() {
+        let session = draft_session();
+        let available = HashSet::from([157, 64, 7]);
+
+        let ban = choose_champion(&session, &ActionType::Ban, &[157, 64, 7], &available);
+        assert_eq!(ban, Some(7));
+    }
+
+    #[test]
+    fn bans_wait_for_the_local_team() {
+        let mut session = draft_session();
+        let available = HashSet::from([157, 7]);
+
+        session.local_player_cell_id = 9;
+        let ban = choose_champion(&session, &ActionType::Ban, &[157, 7], &available);
+        assert_eq!(ban, None);
+
+        session.my_team.clear();
+        let ban = choose_champion(&session, &ActionType::Ban, &[157, 7], &available);
+        assert_eq!(ban, None);
+    }
+
+    #[test]
+    fn role_priorities_fall_back_to_any() {
+        let list = HashMap::from([
+            ("middle".to_string(), vec![1, 2]),
+            ("any".to_string(), vec![3]),
+        ]);
+
+        assert_eq!(AutoPickConfig::priorities(&list, "middle"), vec![1, 2, 3]);
+        assert_eq!(AutoPickConfig::priorities(&list, ""), vec![3]);
+    }
+
+    #[test]
+    fn sessions_are_recorded_once_per_game_and_pick() {
+        let teammate = serde_json::from_value(json!({
+            "cid": "", "game_name": "one", "game_tag": "NA1", "muted": false,
+            "name": "one", "pid": "", "puuid": "one-puuid", "region": ""
+        }))
+        .unwrap();
+        let mut tracker = TeamTracker {
+            active: true,
+            team: vec![teammate],
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            tracker.session_change(42, 0),
+            SessionChange::Game(Some(team)) if team.len() == 1
+        ));
+        assert!(matches!(
+            tracker.session_change(42, 0),
+            SessionChange::Unchanged
+        ));
+        assert!(matches!(
+            tracker.session_change(42, 157),
+            SessionChange::Pick
+        ));
+        assert!(matches!(
+            tracker.session_change(42, 157),
+            SessionChange::Unchanged
+        ));
+
+        tracker.active = false;
+        assert!(matches!(
+            tracker.session_change(43, 157),
+            SessionChange::Game(None)
+        ));
+    }
+
+    #[test]
+    fn pick_turn_is_announced_once_per_action() {
+        let mut state = AutoPickState::default();
+
+        assert!(state.is_new_turn(42, 2));
+        assert!(!state.is_new_turn(42, 2));
+        assert!(state.is_new_turn(42, 3));
+        // Action ids restart in the next game.
+        assert!(state.is_new_turn(43, 3));
+    }
+
+    #[test]
+    fn unexpected_fields_do_not_fail_parsing() {
+        let session: ChampSelectSession = serde_json::from_value(json!({
+            "gameId": 42,
+            "someNewRiotField": { "nested": true },
+            "bans": "not an object anymore",
+            "actions": [[{ "id": 1, "type": "some_new_action" }]],
+            "timer": { "phase": "FINALIZATION", "adjustedTimeLeftInPhase": 5000 }
+        }))
+        .unwrap();
+
+        assert_eq!(session.game_id, 42);
+        assert!(session.bans.my_team_bans.is_empty());
+        assert_eq!(session.actions[0][0].action_type, ActionType::Unknown);
+        assert_eq!(session.timer.phase, "FINALIZATION");
+    }
+
+    #[test]
+    fn bad_elements_only_drop_themselves() {
+        let session: ChampSelectSession = serde_json::from_value(json!({
+            "myTeam": [
+                { "cellId": 0, "championId": 64 },
+                { "cellId": "one", "championId": 157 },
+                { "cellId": 2, "championId": 7 }
+            ],
+            "actions": [
+                [{ "id": 1, "type": "ban" }, { "id": "two", "type": "ban" }],
+                [{ "id": 3, "type": "pick" }]
+            ],
+            "benchChampions": "not a list anymore"
+        }))
+        .unwrap();
+
+        let cells = session
+            .my_team
+            .iter()
+            .map(|p| p.cell_id)
+            .collect::<Vec<_>>();
+        assert_eq!(cells, vec![0, 2]);
+        let turns = session
+            .actions
+            .iter()
+            .map(|turn| turn.iter().map(|a| a.id).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(turns, vec![vec![1], vec![3]]);
+        assert!(session.bench_champions.is_empty());
+    }
+}
@@ -0,0 +1,488 @@
+use crate::{
+    error::{lcu_response, CommandError},
+    lcu::LcuClient,
+    AppConfig, Config, ConfigProfiles, Host,
+};
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever a change to `Config` needs more than `#[serde(default)]`.
+pub const CONFIG_VERSION: u32 = 3;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[n]` upgrades a version `n` file to version `n + 1`.
+const MIGRATIONS: &[Migration] = &[v0_to_v1, v1_to_v2, v2_to_v3];
+
+/// Unversioned files from before migrations existed. Only the first three fields were ever
+/// required, fill in everything a hand edited or partially written file may be missing.
+fn v0_to_v1(cfg: &mut Map<String, Value>) {
+    let defaults = [
+        ("autoOpen", json!(true)),
+        ("autoAccept", json!(true)),
+        ("acceptDelay", json!(2000)),
+        ("multiProvider", json!("opgg")),
+        ("autoReport", json!(false)),
+    ];
+
+    for (key, default) in defaults {
+        cfg.entry(key).or_insert(default);
+    }
+}
+
+/// The single config becomes the default profile.
+fn v1_to_v2(cfg: &mut Map<String, Value>) {
+    let mut profile = std::mem::take(cfg);
+    profile.remove("version");
+
+    cfg.insert("activeProfile".to_string(), json!(DEFAULT_PROFILE));
+    cfg.insert(
+        "profiles".to_string(),
+        json!({ DEFAULT_PROFILE: { "config": profile } }),
+    );
+}
+
+/// `autoReport` filed reports with every category against everyone and was on by default. Its
+/// replacement, `reportReview`, starts off for everyone rather than inheriting it.
+fn v2_to_v3(cfg: &mut Map<String, Value>) {
+    let profiles = cfg.get_mut("profiles").and_then(Value::as_object_mut);
+    for profile in profiles
+        .into_iter()
+        .flat_map(|profiles| profiles.values_mut())
+    {
+        if let Some(config) = profile.get_mut("config").and_then(Value::as_object_mut) {
+            config.remove("autoReport");
+        }
+    }
+}
+
+/// One named set of settings.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    /// Switch to this profile when this account logs in.
+    #[serde(default)]
+    pub puuid: Option<String>,
+    pub config: Config,
+}
+
+/// Listed by `list_profiles`.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSummary {
+    pub name: String,
+    pub puuid: Option<String>,
+    pub active: bool,
+}
+
+/// Everything stored in `config.json`. The active profile's config is also kept in `AppConfig`
+/// so the handlers don't need to look it up.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFile {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    pub version: u32,
+    pub active_profile: String,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Default for ConfigFile {
+    fn default() -> Self {
+        Self {
+            path: None,
+            version: CONFIG_VERSION,
+            active_profile: DEFAULT_PROFILE.to_string(),
+            profiles: BTreeMap::from([(
+                DEFAULT_PROFILE.to_string(),
+                Profile {
+                    puuid: None,
+                    config: Config::default(),
+                },
+            )]),
+        }
+    }
+}
+
+impl ConfigFile {
+    pub fn active_config(&self) -> Config {
+        self.profiles
+            .get(&self.active_profile)
+            .map(|profile| profile.config.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn summaries(&self) -> Vec<ProfileSummary> {
+        self.profiles
+            .iter()
+            .map(|(name, profile)| ProfileSummary {
+                name: name.clone(),
+                puuid: profile.puuid.clone(),
+                active: *name == self.active_profile,
+            })
+            .collect()
+    }
+
+    pub fn set_active_config(&mut self, config: Config) {
+        self.profiles
+            .entry(self.active_profile.clone())
+            .or_insert_with(|| Profile {
+                puuid: None,
+                config: Config::default(),
+            })
+            .config = config;
+    }
+
+    /// Makes `name` the active profile and returns its config.
+    pub fn switch(&mut self, name: &str) -> Result<Config, CommandError> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| CommandError::UnknownProfile(name.to_string()))?;
+
+        self.active_profile = name.to_string();
+        Ok(profile.config.clone())
+    }
+
+    /// Stores `config` as profile `name`, optionally bound to an account. An account can only
+    /// be bound to one profile.
+    pub fn save_profile(&mut self, name: &str, config: Config, puuid: Option<String>) {
+        if let Some(puuid) = &puuid {
+            for profile in self.profiles.values_mut() {
+                if profile.puuid.as_ref() == Some(puuid) {
+                    profile.puuid = None;
+                }
+            }
+        }
+
+        self.profiles
+            .insert(name.to_string(), Profile { puuid, config });
+    }
+
+    /// The profile bound to `puuid`, if any.
+    pub fn profile_for_account(&self, puuid: &str) -> Option<&str> {
+        self.profiles
+            .iter()
+            .find(|(_, profile)| profile.puuid.as_deref() == Some(puuid))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Profiles must not be empty and the active one has to exist.
+    fn validate(&mut self) -> anyhow::Result<()> {
+        if self.profiles.is_empty() {
+            return Err(anyhow!("config has no profiles"));
+        }
+
+        if !self.profiles.contains_key(&self.active_profile) {
+            println!(
+                "Warning: active profile {} does not exist",
+                self.active_profile
+            );
+            self.active_profile = self.profiles.keys().next().cloned().unwrap_or_default();
+        }
+
+        Ok(())
+    }
+
+    pub async fn save(&self) -> Result<(), CommandError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let json = serde_json::to_string(self).map_err(|err| CommandError::Io(err.to_string()))?;
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
+
+    fn save_blocking(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let result = serde_json::to_string(self)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| std::fs::write(path, json).map_err(anyhow::Error::from));
+
+        if let Err(err) = result {
+            println!("Failed to save config: {}", err);
+        }
+    }
+}
+
+/// Switches the active profile, pushes its config to `AppConfig` and announces it as
+/// `config_update`.
+pub async fn activate_profile<H: Host>(host: &H, name: &str) -> Result<Config, CommandError> {
+    let config = {
+        let profiles = host.state::<ConfigProfiles>();
+        let mut profiles = profiles.0.lock().await;
+
+        let config = profiles.switch(name)?;
+        profiles.save().await?;
+
+        let cfg = host.state::<AppConfig>();
+        *cfg.0.lock().await = config.clone();
+        config
+    };
+
+    println!("Switched to profile {}", name);
+    host.emit("config_update", &config);
+
+    Ok(config)
+}
+
+/// Saves `new_cfg` as the active profile's config and applies it, used by `set_config` and
+/// the tray toggles.
+pub async fn update_config<H: Host>(host: &H, new_cfg: Config) -> Result<(), CommandError> {
+    {
+        let profiles = host.state::<ConfigProfiles>();
+        let mut profiles = profiles.0.lock().await;
+        profiles.set_active_config(new_cfg.clone());
+        profiles.save().await?;
+
+        let cfg = host.state::<AppConfig>();
+        *cfg.0.lock().await = new_cfg.clone();
+    }
+
+    host.emit("config_update", &new_cfg);
+    Ok(())
+}
+
+/// Switches to the profile bound to the logged in account, if there is one.
+pub async fn select_profile_for_account<H: Host>(host: &H, remoting_client: &LcuClient) {
+    let Ok(summoner) = lcu_response(
+        remoting_client
+            .get("/lol-summoner/v1/current-summoner".to_string())
+            .await,
+    ) else {
+        return;
+    };
+
+    let Some(puuid) = summoner.get("puuid").and_then(Value::as_str) else {
+        return;
+    };
+
+    let name = {
+        let profiles = host.state::<ConfigProfiles>();
+        let profiles = profiles.0.lock().await;
+        match profiles.profile_for_account(puuid) {
+            Some(name) if name != profiles.active_profile => name.to_string(),
+            _ => return,
+        }
+    };
+
+    if let Err(err) = activate_profile(host, &name).await {
+        println!("Failed to switch to the account's profile: {}", err);
+    }
+}
+
+/// Parses a config file of any known version, returning it along with the version it was
+/// stored as.
+pub fn parse_config(json: &str) -> anyhow::Result<(ConfigFile, u32)> {
+    let mut value: Value = serde_json::from_str(json).context("config is not valid JSON")?;
+    let cfg = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("config is not an object"))?;
+
+    let version = match cfg.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| anyhow!("config version is not a number"))?
+            as u32,
+    };
+
+    // A file written by a newer build is read as is, unknown fields are ignored.
+    for migration in MIGRATIONS.iter().skip(version as usize) {
+        migration(cfg);
+    }
+    cfg.insert("version".to_string(), json!(version.max(CONFIG_VERSION)));
+
+    let mut config: ConfigFile =
+        serde_json::from_value(value).context("config does not match the schema")?;
+    config.validate()?;
+    Ok((config, version))
+}
+
+/// Loads the config at `path`, migrating older files and falling back to the defaults if it
+/// can't be used. The previous file is kept next to it whenever it gets rewritten, an invalid
+/// file is left alone until the config is saved again.
+pub fn load_config(path: &Path) -> ConfigFile {
+    let defaults = ConfigFile {
+        path: Some(path.to_path_buf()),
+        ..Default::default()
+    };
+
+    let json = match std::fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            defaults.save_blocking();
+            return defaults;
+        }
+        Err(err) => {
+            println!("Warning: failed to read config, using defaults: {}", err);
+            return defaults;
+        }
+    };
+
+    match parse_config(&json) {
+        Ok((mut cfg, version)) => {
+            cfg.path = defaults.path;
+            if version < CONFIG_VERSION {
+                println!("Migrating config from v{} to v{}", version, CONFIG_VERSION);
+                backup(path, &format!("v{}.bak", version));
+                cfg.save_blocking();
+            }
+            cfg
+        }
+        Err(err) => {
+            println!("Warning: invalid config, using defaults: {:#}", err);
+            if let Some(backup_path) = backup(path, "invalid") {
+                println!(
+                    "Saving the config will replace it, a copy is kept at {}",
+                    backup_path.display()
+                );
+            }
+            defaults
+        }
+    }
+}
+
+/// `config.json` -> `config.json.<suffix>`, returns the copy's path.
+fn backup(path: &Path, suffix: &str) -> Option<PathBuf> {
+    let mut backup_path = path.as_os_str().to_owned();
+    backup_path.push(format!(".{}", suffix));
+    let backup_path = PathBuf::from(backup_path);
+
+    match std::fs::copy(path, &backup_path) {
+        Ok(_) => Some(backup_path),
+        Err(err) => {
+            println!("Failed to back up config: {}", err);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config(name: &str, contents: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("reveal-config-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("config.json");
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn unversioned_files_are_migrated() {
+        let (cfg, version) =
+            parse_config(r#"{ "autoOpen": false, "autoAccept": true, "acceptDelay": 0 }"#).unwrap();
+
+        assert_eq!(version, 0);
+        assert_eq!(cfg.version, CONFIG_VERSION);
+        assert_eq!(cfg.active_profile, DEFAULT_PROFILE);
+
+        let active = cfg.active_config();
+        assert!(!active.auto_open);
+        assert_eq!(active.multi_provider, "opgg");
+    }
+
+    #[test]
+    fn migration_keeps_a_backup() {
+        let old =
+            r#"{ "version": 1, "autoOpen": false, "autoAccept": false, "acceptDelay": 3000 }"#;
+        let path = temp_config("migrate", old);
+
+        let cfg = load_config(&path);
+        assert_eq!(cfg.active_config().accept_delay, 3000);
+
+        let backup = std::fs::read_to_string(path.with_extension("json.v1.bak")).unwrap();
+        assert_eq!(backup, old);
+
+        let (saved, version) = parse_config(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(version, CONFIG_VERSION);
+        assert_eq!(saved.active_config().accept_delay, 3000);
+
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn auto_report_is_not_carried_over() {
+        let (cfg, version) = parse_config(
+            r#"{
+                "version": 2,
+                "activeProfile": "default",
+                "profiles": { "default": { "config": {
+                    "autoOpen": true, "autoAccept": true, "acceptDelay": 2000, "autoReport": true
+                } } }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(version, 2);
+        assert_eq!(cfg.version, CONFIG_VERSION);
+        assert!(!cfg.active_config().report_review);
+    }
+
+    #[test]
+    fn invalid_files_fall_back_to_defaults() {
+        for (name, contents) in [
+            ("truncated", r#"{ "autoOpen": tr"#),
+            ("wrong-type", r#"{ "version": 1, "autoOpen": "yes" }"#),
+            ("not-an-object", "[]"),
+            (
+                "no-profiles",
+                r#"{ "version": 2, "activeProfile": "a", "profiles": {} }"#,
+            ),
+        ] {
+            let path = temp_config(name, contents);
+
+            let cfg = load_config(&path);
+            assert_eq!(
+                cfg.active_config().accept_delay,
+                Config::default().accept_delay
+            );
+            assert_eq!(
+                std::fs::read_to_string(path.with_extension("json.invalid")).unwrap(),
+                contents
+            );
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), contents);
+
+            let _ = std::fs::remove_dir_all(path.parent().unwrap());
+        }
+    }
+
+    #[test]
+    fn profiles_switch_by_name_and_account() {
+        let mut cfg = ConfigFile::default();
+        cfg.profiles.insert(
+            "sam".to_string(),
+            Profile {
+                puuid: Some("sam-puuid".to_string()),
+                config: Config {
+                    accept_delay: 5000,
+                    ..Default::default()
+                },
+            },
+        );
+
+        assert_eq!(cfg.profile_for_account("sam-puuid"), Some("sam"));
+        assert_eq!(cfg.profile_for_account("someone-else"), None);
+
+        assert_eq!(cfg.switch("sam").unwrap().accept_delay, 5000);
+        assert_eq!(cfg.active_profile, "sam");
+        assert!(matches!(
+            cfg.switch("nobody"),
+            Err(CommandError::UnknownProfile(_))
+        ));
+        assert_eq!(cfg.active_profile, "sam");
+    }
+}
@@ -0,0 +1,368 @@
+use crate::{
+    config::select_profile_for_account,
+    handle_ws_message,
+    lcu::{ConnectionSource, LcuConnection, LcuEvent, LcuWebsocket},
+    recording::{EventRecorder, EventReplay, EventSource},
+    state::{get_gameflow_state, handle_client_state},
+    AppConfig, Host, ManagedGameflowState, LCU,
+};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const GAMEFLOW_PHASE: &str = "/lol-gameflow/v1/gameflow-phase";
+const CHAMP_SELECT_SESSION: &str = "/lol-champ-select/v1/session";
+const READY_CHECK: &str = "/lol-matchmaking/v1/ready-check";
+const CHAT_PARTICIPANTS: &str = "/chat/v5/participants";
+
+const SEARCH_INTERVAL: Duration = Duration::from_secs(2);
+const PROCESS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Emitted to the frontend as `connection_state_update`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ConnectionState {
+    /// No League client process was found.
+    #[default]
+    Searching,
+    /// Found the client, reading its auth info and opening the websocket.
+    Authenticating,
+    Connected,
+    /// The client is running but its API or websocket isn't reachable yet, retrying.
+    Degraded,
+    /// The websocket closed or the client exited.
+    Disconnected,
+}
+
+/// Exponential backoff between `min` and `max`.
+#[derive(Debug)]
+pub struct Backoff {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl Backoff {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            max,
+            current: min,
+        }
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(self.max);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.current = self.min;
+    }
+}
+
+/// Owns the League client connection for the lifetime of the app: finds the client, connects,
+/// routes events to the handlers and starts over whenever the client goes away.
+pub struct ConnectionSupervisor<H: Host> {
+    host: H,
+    source: ConnectionSource,
+    replay: Option<(PathBuf, f64)>,
+    state: Option<ConnectionState>,
+}
+
+impl<H: Host> ConnectionSupervisor<H> {
+    pub fn new(host: H, source: ConnectionSource) -> Self {
+        let replay = EventReplay::from_env();
+        let source = match replay {
+            Some(_) => ConnectionSource::Replay,
+            None => source,
+        };
+
+        Self {
+            host,
+            source,
+            replay,
+            state: None,
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(30));
+
+        loop {
+            let Some(args) = self.source.args() else {
+                self.set_state(ConnectionState::Searching).await;
+                backoff.reset();
+                tokio::time::sleep(SEARCH_INTERVAL).await;
+                continue;
+            };
+
+            self.set_state(ConnectionState::Authenticating).await;
+
+            let connection = match self.source.connect(args) {
+                Ok(connection) => connection,
+                Err(err) => {
+                    println!("Failed to read League Client auth info: {}", err);
+                    self.retry(&mut backoff).await;
+                    continue;
+                }
+            };
+
+            self.attach(&connection).await;
+
+            let events = match &self.replay {
+                Some((path, speed)) => {
+                    println!("Replaying {} at {}x", path.display(), speed);
+                    EventReplay::open(path, *speed)
+                        .await
+                        .map(EventSource::Replay)
+                }
+                None => open_websocket(&connection)
+                    .await
+                    .map(|ws| EventSource::Live(ws, None)),
+            };
+
+            let mut events = match events {
+                Ok(events) => events,
+                Err(err) => {
+                    println!("Failed to open League Client event stream: {}", err);
+                    self.retry(&mut backoff).await;
+                    continue;
+                }
+            };
+
+            // Champ select participants only come through the Riot client, without it the team
+            // is still read once when champ select starts.
+            if let EventSource::Live(_, riot) = &mut events {
+                *riot = open_riot_websocket(&connection).await.map(Box::new);
+            }
+
+            backoff.reset();
+            self.set_state(ConnectionState::Connected).await;
+            println!("Connected to League Client WebSocket!");
+
+            self.pump(&mut events, &connection).await;
+
+            self.detach().await;
+            self.set_state(ConnectionState::Disconnected).await;
+
+            if self.replay.is_some() {
+                println!("Replay finished");
+                return;
+            }
+        }
+    }
+
+    async fn retry(&mut self, backoff: &mut Backoff) {
+        self.set_state(ConnectionState::Degraded).await;
+        tokio::time::sleep(backoff.next_delay()).await;
+    }
+
+    /// Routes events until the stream closes or the client process exits.
+    async fn pump(&self, events: &mut EventSource, connection: &LcuConnection) {
+        let replaying = self.replay.is_some();
+        let mut recorder = if replaying {
+            None
+        } else {
+            self.start_recording().await
+        };
+
+        if !replaying {
+            select_profile_for_account(&self.host, &connection.remoting_client).await;
+
+            match get_gameflow_state(&connection.remoting_client).await {
+                Some(state) => {
+                    if let Some(recorder) = recorder.as_mut() {
+                        let snapshot = LcuEvent::snapshot(GAMEFLOW_PHASE, serde_json::json!(state));
+                        let _ = recorder.record(&snapshot).await;
+                    }
+
+                    handle_client_state(
+                        state,
+                        &self.host,
+                        &connection.remoting_client,
+                        &connection.app_client,
+                    )
+                    .await;
+                }
+                None => println!("Failed to fetch initial gameflow phase"),
+            }
+        }
+
+        let mut process_check = tokio::time::interval(PROCESS_CHECK_INTERVAL);
+        process_check.tick().await;
+
+        loop {
+            tokio::select! {
+                msg = events.next() => {
+                    let Some(msg) = msg else {
+                        println!("League Client event stream closed");
+                        return;
+                    };
+
+                    if let Some(recorder) = recorder.as_mut() {
+                        if let Err(err) = recorder.record(&msg).await {
+                            println!("Failed to record event: {}", err);
+                        }
+                    }
+
+                    handle_ws_message(
+                        msg,
+                        &self.host,
+                        &connection.remoting_client,
+                        &connection.app_client,
+                    )
+                    .await;
+                }
+
+                // A replay can't be interrupted mid-sleep without losing an event.
+                _ = process_check.tick(), if !replaying => {
+                    if self.source.args().is_none() {
+                        println!("League Client exited");
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn start_recording(&self) -> Option<EventRecorder> {
+        let record = {
+            let cfg = self.host.state::<AppConfig>();
+            let cfg = cfg.0.lock().await;
+            cfg.record_sessions
+        };
+
+        if !record {
+            return None;
+        }
+
+        let dir = self.host.log_dir()?;
+        match EventRecorder::create(&dir.join("recordings")).await {
+            Ok(recorder) => {
+                println!("Recording session to {}", recorder.path().display());
+                Some(recorder)
+            }
+            Err(err) => {
+                println!("Failed to start session recording: {}", err);
+                None
+            }
+        }
+    }
+
+    async fn attach(&self, connection: &LcuConnection) {
+        {
+            let lcu_state = self.host.state::<LCU>();
+            let mut guard = lcu_state.0.lock().await;
+            guard.data = Some(connection.info.clone());
+            guard.clients = Some(connection.clients());
+        }
+
+        let gameflow = self.host.state::<ManagedGameflowState>();
+        gameflow.0.lock().await.reset();
+    }
+
+    async fn detach(&self) {
+        {
+            let lcu_state = self.host.state::<LCU>();
+            let mut guard = lcu_state.0.lock().await;
+            guard.connected = false;
+            guard.data = None;
+            guard.clients = None;
+        }
+
+        let gameflow = self.host.state::<ManagedGameflowState>();
+        gameflow.0.lock().await.reset();
+    }
+
+    async fn set_state(&mut self, state: ConnectionState) {
+        if self.state == Some(state) {
+            return;
+        }
+
+        println!("League Client connection: {:?}", state);
+
+        let was_connected = self.state == Some(ConnectionState::Connected);
+        let connected = state == ConnectionState::Connected;
+        self.state = Some(state);
+
+        {
+            let lcu_state = self.host.state::<LCU>();
+            let mut guard = lcu_state.0.lock().await;
+            guard.connected = connected;
+            guard.connection_state = state;
+        }
+
+        self.host.emit("connection_state_update", state);
+        if was_connected != connected || state == ConnectionState::Searching {
+            self.host.emit("lcu_state_update", connected);
+        }
+    }
+}
+
+async fn open_websocket(connection: &LcuConnection) -> anyhow::Result<LcuWebsocket> {
+    let mut ws = LcuWebsocket::connect(&connection.auth).await?;
+    ws.subscribe(GAMEFLOW_PHASE).await?;
+    ws.subscribe(CHAMP_SELECT_SESSION).await?;
+    ws.subscribe(READY_CHECK).await?;
+    Ok(ws)
+}
+
+async fn open_riot_websocket(connection: &LcuConnection) -> Option<LcuWebsocket> {
+    let auth = connection.riot_auth.as_ref()?;
+
+    let ws = async {
+        let mut ws = LcuWebsocket::connect(auth).await?;
+        ws.subscribe(CHAT_PARTICIPANTS).await?;
+        anyhow::Ok(ws)
+    };
+
+    match ws.await {
+        Ok(ws) => Some(ws),
+        Err(err) => {
+            println!("Failed to open Riot Client event stream: {}", err);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::lcu_response;
+    use serde_json::json;
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+
+        let delays = (0..5)
+            .map(|_| backoff.next_delay().as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn replay_never_sends_requests() {
+        let source = ConnectionSource::Replay;
+        let connection = source.connect(source.args().unwrap()).unwrap();
+
+        let err = lcu_response(
+            connection
+                .remoting_client
+                .post(
+                    "/lol-matchmaking/v1/ready-check/accept".to_string(),
+                    json!({}),
+                )
+                .await,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "League client request failed: /lol-matchmaking/v1/ready-check/accept not sent while replaying"
+        );
+    }
+}
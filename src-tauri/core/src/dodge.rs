@@ -0,0 +1,174 @@
+use crate::{
+    champ_select::ChampSelectSession,
+    dodge_history::{record_dodge, DodgeKind},
+    error::{lcu_response, CommandError},
+    lcu::LcuClient,
+    notifications::{notify, NotifyEvent},
+    AppConfig, Host, ManagedDodgeState,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+const QUIT_CHAMP_SELECT: &str = "/lol-login/v1/session/invoke?destination=lcdsServiceProxy&method=call&args=[\"\",\"teambuilder-draft\",\"quitV2\",\"\"]";
+
+/// A last second dodge waiting to fire, aborted through `handle`.
+pub struct ScheduledDodge {
+    pub game_id: u64,
+    pub handle: JoinHandle<()>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DodgeScheduled {
+    game_id: u64,
+    fires_in_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DodgeCancelled {
+    game_id: u64,
+    reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DodgeSent {
+    game_id: u64,
+    error: Option<CommandError>,
+}
+
+pub async fn send_dodge(remoting_client: &LcuClient) -> Result<Value, CommandError> {
+    lcu_response(
+        remoting_client
+            .post(QUIT_CHAMP_SELECT.to_string(), serde_json::json!({}))
+            .await,
+    )
+}
+
+/// Schedules the dodge `dodge_lead_time` ms before finalization ends, if one was enabled for
+/// this game and none is pending yet.
+pub async fn schedule_dodge<H: Host>(
+    session: &ChampSelectSession,
+    host: &H,
+    remoting_client: &LcuClient,
+) {
+    let game_id = session.game_id;
+    let lead_time = {
+        let cfg = host.state::<AppConfig>();
+        let cfg = cfg.0.lock().await;
+        cfg.dodge_lead_time
+    };
+
+    let dodge_state = host.state::<ManagedDodgeState>();
+    let mut dodge_state = dodge_state.0.lock().await;
+
+    if dodge_state.enabled != Some(game_id) || dodge_state.last_dodge == Some(game_id) {
+        return;
+    }
+
+    if dodge_state.scheduled.as_ref().map(|s| s.game_id) == Some(game_id) {
+        return;
+    }
+
+    let delay = session
+        .timer
+        .adjusted_time_left_in_phase
+        .saturating_sub(lead_time);
+    println!("Scheduling finalization dodge in {}ms", delay);
+
+    let cloned_host = host.clone();
+    let cloned_remoting = remoting_client.clone();
+    let handle = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        fire_dodge(game_id, &cloned_host, &cloned_remoting).await;
+    });
+
+    if let Some(previous) = dodge_state
+        .scheduled
+        .replace(ScheduledDodge { game_id, handle })
+    {
+        previous.handle.abort();
+    }
+
+    host.emit(
+        "dodge_scheduled",
+        DodgeScheduled {
+            game_id,
+            fires_in_ms: delay,
+        },
+    );
+}
+
+/// Aborts a pending dodge, if there is one.
+pub async fn cancel_dodge<H: Host>(host: &H, reason: &str) {
+    let scheduled = {
+        let dodge_state = host.state::<ManagedDodgeState>();
+        let mut dodge_state = dodge_state.0.lock().await;
+        dodge_state.scheduled.take()
+    };
+
+    if let Some(scheduled) = scheduled {
+        scheduled.handle.abort();
+        emit_cancelled(host, scheduled.game_id, reason);
+    }
+}
+
+fn emit_cancelled<H: Host>(host: &H, game_id: u64, reason: &str) {
+    println!("Dodge for game {} cancelled: {}", game_id, reason);
+    host.emit(
+        "dodge_cancelled",
+        DodgeCancelled {
+            game_id,
+            reason: reason.to_string(),
+        },
+    );
+}
+
+/// Re-checks that we're still finalizing the same game with the dodge enabled, then quits.
+async fn fire_dodge<H: Host>(game_id: u64, host: &H, remoting: &LcuClient) {
+    let session = lcu_response(
+        remoting
+            .get("/lol-champ-select/v1/session".to_string())
+            .await,
+    )
+    .ok()
+    .and_then(|s| serde_json::from_value::<ChampSelectSession>(s).ok());
+
+    let dodge_state = host.state::<ManagedDodgeState>();
+    let mut dodge_state = dodge_state.0.lock().await;
+    dodge_state.scheduled = None;
+
+    let reason = match session {
+        None => Some("champ select ended"),
+        Some(s) if s.game_id != game_id => Some("champ select changed"),
+        Some(s) if s.timer.phase != "FINALIZATION" => Some("no longer in finalization"),
+        Some(_) if dodge_state.enabled != Some(game_id) => Some("dodge disabled"),
+        Some(_) => None,
+    };
+
+    if let Some(reason) = reason {
+        emit_cancelled(host, game_id, reason);
+        return;
+    }
+
+    dodge_state.last_dodge = Some(game_id);
+    drop(dodge_state);
+
+    println!("Sending dodge…");
+    let error = send_dodge(remoting).await.err();
+    let body = match &error {
+        None => "Left champ select before the game started".to_string(),
+        Some(err) => format!("The dodge failed: {}", err),
+    };
+    notify(host, NotifyEvent::Dodge, "Last second dodge", &body).await;
+
+    let sent = error.is_none();
+    host.emit("dodge_sent", DodgeSent { game_id, error });
+
+    if sent {
+        record_dodge(host, remoting, game_id, DodgeKind::Scheduled).await;
+    }
+}
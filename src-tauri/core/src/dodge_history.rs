@@ -0,0 +1,343 @@
+use crate::{
+    error::lcu_response,
+    lcu::LcuClient,
+    match_db::{query_db, with_db},
+    utils::lenient_elements,
+    Host,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+
+/// The search error matchmaking reports for a dodge lockout.
+const QUEUE_DODGER: &str = "QUEUE_DODGER";
+
+/// Dodges older than this no longer count towards the penalty tier.
+pub const PENALTY_WINDOW_MS: u64 = 24 * HOUR_MS;
+
+/// Queue lockout by number of dodges inside the window, the last tier repeats.
+pub const PENALTY_TIERS: &[PenaltyTier] = &[
+    PenaltyTier {
+        min_dodges: 1,
+        lockout_minutes: 6,
+    },
+    PenaltyTier {
+        min_dodges: 2,
+        lockout_minutes: 30,
+    },
+    PenaltyTier {
+        min_dodges: 3,
+        lockout_minutes: 12 * 60,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PenaltyTier {
+    pub min_dodges: usize,
+    pub lockout_minutes: u64,
+}
+
+impl PenaltyTier {
+    /// The tier `dodges` dodges inside the window put us in.
+    fn for_dodges(dodges: usize) -> Option<Self> {
+        PENALTY_TIERS
+            .iter()
+            .rev()
+            .find(|t| dodges >= t.min_dodges)
+            .copied()
+    }
+
+    /// The lowest tier whose lockout can still have `remaining_secs` left.
+    fn for_lockout(remaining_secs: f64) -> Self {
+        PENALTY_TIERS
+            .iter()
+            .find(|t| (t.lockout_minutes * 60) as f64 >= remaining_secs)
+            .or(PENALTY_TIERS.last())
+            .copied()
+            .unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DodgeKind {
+    /// Sent through the `dodge` command.
+    Manual,
+    /// The last second dodge fired by the scheduler.
+    Scheduled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DodgeRecord {
+    pub game_id: u64,
+    pub queue_id: Option<i64>,
+    /// Milliseconds since the unix epoch.
+    pub timestamp: u64,
+    pub kind: DodgeKind,
+}
+
+/// A queue lockout from the `errors` of `/lol-matchmaking/v1/search`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuePenalty {
+    #[serde(default)]
+    pub error_type: String,
+    /// Can be a party member rather than the local player.
+    #[serde(default)]
+    pub penalized_summoner_id: u64,
+    /// Seconds left when the search state was read.
+    pub penalty_time_remaining: f64,
+}
+
+/// Where the player stands with dodge penalties, emitted as `dodge_status_update`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PenaltyStatus {
+    /// Dodges inside the window, at least as many as our running lockout implies.
+    pub dodges_in_window: usize,
+    pub current_tier: Option<PenaltyTier>,
+    /// The lockout the next dodge would cause.
+    pub next_dodge_tier: PenaltyTier,
+    /// `None` once the highest tier is reached.
+    pub dodges_until_next_tier: Option<usize>,
+    /// When the oldest recorded dodge in the window stops counting.
+    pub window_resets_at: Option<u64>,
+    /// Lockouts matchmaking reports right now, empty when queueing is possible.
+    pub penalties: Vec<QueuePenalty>,
+    /// When the longest lockout ends.
+    pub penalty_expires_at: Option<u64>,
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Puts the dodges in the window on a tier, `dodge_times` being every recorded dodge in it.
+///
+/// Dodges made without Reveal aren't in the history, so a lockout matchmaking reports for
+/// `summoner_id`, or for anyone when it's unknown, raises the count to the tier it belongs to.
+pub fn penalty_status(
+    dodge_times: &[u64],
+    now: u64,
+    penalties: Vec<QueuePenalty>,
+    summoner_id: Option<u64>,
+) -> PenaltyStatus {
+    let window_start = now.saturating_sub(PENALTY_WINDOW_MS);
+    let in_window = dodge_times
+        .iter()
+        .filter(|t| **t > window_start)
+        .collect::<Vec<_>>();
+
+    let locked_out = penalties
+        .iter()
+        .filter(|p| p.error_type == QUEUE_DODGER)
+        .filter(|p| summoner_id.is_none_or(|id| p.penalized_summoner_id == id))
+        .map(|p| PenaltyTier::for_lockout(p.penalty_time_remaining).min_dodges)
+        .max()
+        .unwrap_or_default();
+
+    let count = in_window.len().max(locked_out);
+    let dodges_until_next_tier = PENALTY_TIERS
+        .iter()
+        .find(|t| t.min_dodges > count)
+        .map(|t| t.min_dodges - count);
+    let penalty_expires_at = penalties
+        .iter()
+        .map(|p| now + (p.penalty_time_remaining * 1000.0) as u64)
+        .max();
+
+    PenaltyStatus {
+        dodges_in_window: count,
+        current_tier: PenaltyTier::for_dodges(count),
+        next_dodge_tier: PenaltyTier::for_dodges(count + 1).unwrap_or(PENALTY_TIERS[0]),
+        dodges_until_next_tier,
+        window_resets_at: in_window.iter().map(|t| *t + PENALTY_WINDOW_MS).min(),
+        penalties,
+        penalty_expires_at,
+    }
+}
+
+/// Reads our dodges from the match database and, while connected, the lockouts from the client.
+pub async fn dodge_status<H: Host>(host: &H, remoting_client: Option<&LcuClient>) -> PenaltyStatus {
+    let (penalties, summoner_id) = match remoting_client {
+        Some(remoting_client) => (
+            fetch_penalties(remoting_client).await,
+            fetch_summoner_id(remoting_client).await,
+        ),
+        None => (Vec::new(), None),
+    };
+
+    let now = now_ms();
+    let window_start = now.saturating_sub(PENALTY_WINDOW_MS);
+    let dodge_times = query_db(host, move |db| db.dodge_times_since(window_start)).await;
+    let dodge_times = dodge_times.unwrap_or_else(|err| {
+        println!("Failed to read the dodge history: {}", err);
+        Vec::new()
+    });
+
+    penalty_status(&dodge_times, now, penalties, summoner_id)
+}
+
+/// Reads the running queue lockouts from `/lol-matchmaking/v1/search`.
+pub async fn fetch_penalties(remoting_client: &LcuClient) -> Vec<QueuePenalty> {
+    let search = lcu_response(
+        remoting_client
+            .get("/lol-matchmaking/v1/search".to_string())
+            .await,
+    );
+
+    match search {
+        Ok(search) => active_penalties(&search),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn active_penalties(search: &Value) -> Vec<QueuePenalty> {
+    lenient_elements::<QueuePenalty>(search["errors"].clone())
+        .into_iter()
+        .filter(|p| p.penalty_time_remaining > 0.0)
+        .collect()
+}
+
+async fn fetch_summoner_id(remoting_client: &LcuClient) -> Option<u64> {
+    let summoner = remoting_client
+        .get("/lol-summoner/v1/current-summoner".to_string())
+        .await
+        .ok()?;
+    summoner.get("summonerId")?.as_u64()
+}
+
+pub async fn fetch_queue_id(remoting_client: &LcuClient) -> Option<i64> {
+    let session = remoting_client
+        .get("/lol-gameflow/v1/session".to_string())
+        .await
+        .ok()?;
+    session.pointer("/gameData/queue/id")?.as_i64()
+}
+
+/// Logs a dodge that was just sent and pushes the new penalty status to the frontend.
+pub async fn record_dodge<H: Host>(
+    host: &H,
+    remoting_client: &LcuClient,
+    game_id: u64,
+    kind: DodgeKind,
+) {
+    let record = DodgeRecord {
+        game_id,
+        queue_id: fetch_queue_id(remoting_client).await,
+        timestamp: now_ms(),
+        kind,
+    };
+
+    with_db(host, "dodge", move |db| db.record_dodge(&record)).await;
+
+    let status = dodge_status(host, Some(remoting_client)).await;
+    host.emit("dodge_status_update", status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dodges(ages_in_hours: &[u64], now: u64) -> Vec<u64> {
+        ages_in_hours
+            .iter()
+            .map(|age| now - age * HOUR_MS)
+            .collect()
+    }
+
+    #[test]
+    fn tiers_follow_the_dodges_in_the_window() {
+        let now = 100 * HOUR_MS;
+        let tier = |i: usize| Some(PENALTY_TIERS[i]);
+
+        let expected = [
+            (0, None, PENALTY_TIERS[0], Some(1)),
+            (1, tier(0), PENALTY_TIERS[1], Some(1)),
+            (2, tier(1), PENALTY_TIERS[2], Some(1)),
+            (3, tier(2), PENALTY_TIERS[2], None),
+            (4, tier(2), PENALTY_TIERS[2], None),
+        ];
+
+        for (count, current, next, until_next) in expected {
+            let ages = (1..=count as u64).collect::<Vec<_>>();
+            let status = penalty_status(&dodges(&ages, now), now, Vec::new(), None);
+
+            assert_eq!(status.dodges_in_window, count);
+            assert_eq!(status.current_tier, current);
+            assert_eq!(status.next_dodge_tier, next);
+            assert_eq!(status.dodges_until_next_tier, until_next);
+        }
+    }
+
+    #[test]
+    fn old_dodges_leave_the_window() {
+        let now = 100 * HOUR_MS;
+        let status = penalty_status(&dodges(&[2, 23, 30], now), now, Vec::new(), None);
+
+        assert_eq!(status.dodges_in_window, 2);
+        assert_eq!(status.current_tier, Some(PENALTY_TIERS[1]));
+        assert_eq!(status.window_resets_at, Some(now + HOUR_MS));
+        assert!(status.penalties.is_empty());
+        assert_eq!(status.penalty_expires_at, None);
+    }
+
+    #[test]
+    fn our_lockout_raises_the_tier() {
+        let penalty = |summoner: u64, secs: f64| QueuePenalty {
+            error_type: QUEUE_DODGER.to_string(),
+            penalized_summoner_id: summoner,
+            penalty_time_remaining: secs,
+        };
+
+        // 20 minutes left can only be the 30 minute lockout, so we dodged twice.
+        let now = 100 * HOUR_MS;
+        let penalties = vec![penalty(7, 1200.0), penalty(8, 40000.0)];
+        let status = penalty_status(&dodges(&[1], now), now, penalties.clone(), Some(7));
+
+        assert_eq!(status.dodges_in_window, 2);
+        assert_eq!(status.current_tier, Some(PENALTY_TIERS[1]));
+        assert_eq!(status.dodges_until_next_tier, Some(1));
+        assert_eq!(status.window_resets_at, Some(now + 23 * HOUR_MS));
+        assert_eq!(status.penalty_expires_at, Some(now + 40000 * 1000));
+
+        // Without knowing who we are, the party member's lockout counts too.
+        let status = penalty_status(&[], now, penalties, None);
+        assert_eq!(status.dodges_in_window, 3);
+        assert_eq!(status.dodges_until_next_tier, None);
+        assert_eq!(status.window_resets_at, None);
+    }
+
+    #[test]
+    fn penalties_come_from_the_search_state() {
+        let search = serde_json::json!({
+            "errors": [
+                { "errorType": "QUEUE_DODGER", "id": 1, "penalizedSummonerId": 7,
+                  "penaltyTimeRemaining": 300.0 },
+                { "errorType": "QUEUE_DODGER", "id": 2, "penalizedSummonerId": 8,
+                  "penaltyTimeRemaining": 1800.0 },
+                { "errorType": "QUEUE_DODGER", "id": 3, "penaltyTimeRemaining": 0.0 },
+                { "errorType": "SOMETHING_ELSE" }
+            ]
+        });
+
+        let now = 100 * HOUR_MS;
+        let status = penalty_status(&dodges(&[1], now), now, active_penalties(&search), Some(7));
+
+        let summoners = status
+            .penalties
+            .iter()
+            .map(|p| p.penalized_summoner_id)
+            .collect::<Vec<_>>();
+        assert_eq!(summoners, vec![7, 8]);
+        assert_eq!(status.dodges_in_window, 1);
+        assert_eq!(status.penalty_expires_at, Some(now + 1800 * 1000));
+    }
+}
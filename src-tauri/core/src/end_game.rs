@@ -0,0 +1,510 @@
+use crate::{
+    dodge_history::now_ms,
+    error::{lcu_response, parse_response, CommandError},
+    lcu::LcuClient,
+    match_db::with_db,
+    utils::{lenient, lenient_slots, lenient_vec},
+    AppConfig, Host, ManagedReportState,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// What a report can be filed for, only ever chosen by the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReportCategory {
+    NegativeAttitude,
+    VerbalAbuse,
+    LeavingAfk,
+    AssistingEnemyTeam,
+    HateSpeech,
+    ThirdPartyTools,
+    InappropriateName,
+}
+
+/// Someone from the last game the user can choose to report.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportCandidate {
+    pub summoner_id: u64,
+    pub puuid: String,
+    pub game_name: String,
+    pub tag_line: String,
+    pub champion_id: i64,
+    pub teammate: bool,
+    pub friend: bool,
+    /// Already reported through `submit_report`.
+    pub reported: bool,
+}
+
+/// Everyone but us from the last game, emitted as `report_queue_update`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportQueue {
+    pub game_id: u64,
+    pub players: Vec<ReportCandidate>,
+}
+
+/// A report the user reviewed, sent by `submit_report`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportRequest {
+    pub game_id: u64,
+    pub puuid: String,
+    pub categories: Vec<ReportCategory>,
+    #[serde(default)]
+    pub comment: String,
+}
+
+/// `/lol-end-of-game/v1/eog-stats-block`, plus the LP and mastery changes from their own
+/// endpoints.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EndOfGameStats {
+    #[serde(deserialize_with = "string_or_number")]
+    pub game_id: u64,
+    /// In seconds.
+    pub game_length: u64,
+    pub game_mode: String,
+    pub queue_type: String,
+    #[serde(deserialize_with = "lenient")]
+    pub queue_id: Option<i64>,
+    #[serde(deserialize_with = "lenient")]
+    pub local_player: EndOfGamePlayer,
+    #[serde(deserialize_with = "lenient_vec")]
+    pub teams: Vec<EndOfGameTeam>,
+    #[serde(skip_deserializing)]
+    pub lp_change: Option<LpChange>,
+    #[serde(skip_deserializing)]
+    pub mastery_change: Option<MasteryChange>,
+}
+
+/// The client has sent `gameId` both as a number and as a string, anything else is 0.
+fn string_or_number<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Number(id) => id.as_u64().unwrap_or_default(),
+        Value::String(id) => id.trim().parse().unwrap_or_default(),
+        _ => 0,
+    })
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EndOfGameTeam {
+    pub team_id: i64,
+    pub is_player_team: bool,
+    pub is_winning_team: bool,
+    #[serde(deserialize_with = "lenient_vec")]
+    pub players: Vec<EndOfGamePlayer>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EndOfGamePlayer {
+    #[serde(deserialize_with = "lenient")]
+    pub summoner_id: u64,
+    pub puuid: String,
+    pub riot_id_game_name: String,
+    pub riot_id_tag_line: String,
+    pub champion_id: i64,
+    pub champion_name: String,
+    pub team_id: i64,
+    /// Item ids by slot, empty or unreadable slots are 0. The last one is the trinket.
+    #[serde(deserialize_with = "lenient_slots")]
+    pub items: Vec<i64>,
+    #[serde(deserialize_with = "lenient")]
+    pub stats: PlayerStats,
+}
+
+/// The few of the client's `SCREAMING_CASE` stat keys the UI shows, sent to it in camelCase.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all(serialize = "camelCase"), default)]
+pub struct PlayerStats {
+    #[serde(rename(deserialize = "CHAMPIONS_KILLED"))]
+    pub kills: u32,
+    #[serde(rename(deserialize = "NUM_DEATHS"))]
+    pub deaths: u32,
+    #[serde(rename(deserialize = "ASSISTS"))]
+    pub assists: u32,
+    #[serde(rename(deserialize = "GOLD_EARNED"))]
+    pub gold_earned: u32,
+    #[serde(rename(deserialize = "TOTAL_DAMAGE_DEALT_TO_CHAMPIONS"))]
+    pub damage_to_champions: u32,
+    #[serde(rename(deserialize = "TOTAL_DAMAGE_TAKEN"))]
+    pub damage_taken: u32,
+    #[serde(rename(deserialize = "MINIONS_KILLED"))]
+    pub minions_killed: u32,
+    #[serde(rename(deserialize = "NEUTRAL_MINIONS_KILLED"))]
+    pub neutral_minions_killed: u32,
+    #[serde(rename(deserialize = "VISION_SCORE"))]
+    pub vision_score: u32,
+}
+
+/// `/lol-ranked/v1/current-lp-change-notification`, only there after ranked games.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LpChange {
+    pub queue_type: String,
+    pub league_points_delta: i64,
+    pub league_points: i64,
+    pub tier: String,
+    pub division: String,
+}
+
+/// `/lol-end-of-game/v1/champion-mastery-updates`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MasteryChange {
+    pub champion_id: i64,
+    pub champion_level: i64,
+    pub points_before_game: i64,
+    pub points_gained: i64,
+    pub level_up: bool,
+}
+
+impl PlayerStats {
+    /// `(kills + assists) / deaths`, deathless games count as one death.
+    pub fn kda(&self) -> f64 {
+        (self.kills + self.assists) as f64 / self.deaths.max(1) as f64
+    }
+}
+
+impl EndOfGameStats {
+    pub fn player_team(&self) -> Option<&EndOfGameTeam> {
+        self.teams.iter().find(|team| team.is_player_team)
+    }
+
+    /// `None` if the block has no team marked as ours.
+    pub fn won(&self) -> Option<bool> {
+        self.player_team().map(|team| team.is_winning_team)
+    }
+}
+
+fn parse_u64_from_value(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(num) => num.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+async fn fetch_friend_ids(app_client: &LcuClient) -> Result<HashSet<u64>, String> {
+    let response = app_client
+        .get("/lol-chat/v1/friends".to_string())
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+
+    let mut ids = HashSet::new();
+
+    if let Some(arr) = response.as_array() {
+        for f in arr {
+            if let Some(id) = f.get("summonerId").and_then(parse_u64_from_value) {
+                ids.insert(id);
+            }
+        }
+    }
+
+    Ok(ids)
+}
+
+pub async fn handle_end_game_start_<H: Host>(
+    host: H,
+    app_client: LcuClient,
+    remoting_client: LcuClient,
+) {
+    let response = match remoting_client
+        .get("/lol-end-of-game/v1/eog-stats-block".to_string())
+        .await
+    {
+        Ok(data) => data,
+        Err(_) => return,
+    };
+
+    let mut stats: EndOfGameStats = match parse_response(response.clone()) {
+        Ok(stats) => stats,
+        Err(err) => {
+            println!("Failed to parse end of game stats: {}", err);
+            return;
+        }
+    };
+
+    let game_id = stats.game_id;
+    if game_id == 0 {
+        println!(
+            "End of game stats have no usable gameId: {}",
+            response.get("gameId").unwrap_or(&Value::Null)
+        );
+        return;
+    }
+
+    {
+        let state = host.state::<ManagedReportState>();
+        let mut guard = state.0.lock().await;
+        if guard.last_report == Some(game_id) {
+            return;
+        }
+        guard.last_report = Some(game_id);
+    }
+
+    stats.lp_change = fetch_optional(
+        &remoting_client,
+        "/lol-ranked/v1/current-lp-change-notification",
+    )
+    .await;
+    stats.mastery_change = fetch_optional(
+        &remoting_client,
+        "/lol-end-of-game/v1/champion-mastery-updates",
+    )
+    .await;
+
+    let recorded = stats.clone();
+    with_db(&host, "end of game stats", move |db| {
+        db.record_end_of_game(&recorded, &response, now_ms())
+    })
+    .await;
+
+    {
+        let state = host.state::<ManagedReportState>();
+        state.0.lock().await.last_game = Some(stats.clone());
+    }
+    host.emit("end_of_game_stats", &stats);
+
+    let report_review = {
+        let cfg_state = host.state::<AppConfig>();
+        let cfg = cfg_state.0.lock().await;
+        cfg.report_review
+    };
+
+    if !report_review {
+        return;
+    }
+
+    let friend_ids = fetch_friend_ids(&app_client).await.unwrap_or_default();
+    let queue = report_queue(&stats, &friend_ids);
+
+    let state = host.state::<ManagedReportState>();
+    state.0.lock().await.queue = Some(queue.clone());
+    host.emit("report_queue_update", queue);
+}
+
+/// Reads an endpoint that only has data after some games, `None` when it doesn't.
+async fn fetch_optional<T: DeserializeOwned>(remoting_client: &LcuClient, path: &str) -> Option<T> {
+    let response = lcu_response(remoting_client.get(path.to_string()).await).ok()?;
+    parse_response(response).ok()
+}
+
+/// Lists everyone in the stats block except the local player. Nobody is preselected.
+pub fn report_queue(stats: &EndOfGameStats, friend_ids: &HashSet<u64>) -> ReportQueue {
+    let players = stats
+        .teams
+        .iter()
+        .flat_map(|team| team.players.iter().map(move |player| (team, player)))
+        .filter(|(_, player)| {
+            player.summoner_id != 0
+                && !player.puuid.is_empty()
+                && player.summoner_id != stats.local_player.summoner_id
+        })
+        .map(|(team, player)| ReportCandidate {
+            summoner_id: player.summoner_id,
+            puuid: player.puuid.clone(),
+            game_name: player.riot_id_game_name.clone(),
+            tag_line: player.riot_id_tag_line.clone(),
+            champion_id: player.champion_id,
+            teammate: team.is_player_team,
+            friend: friend_ids.contains(&player.summoner_id),
+            reported: false,
+        })
+        .collect();
+
+    ReportQueue {
+        game_id: stats.game_id,
+        players,
+    }
+}
+
+/// Files one reviewed report from the queue and marks the player as reported.
+pub async fn submit_report<H: Host>(
+    host: &H,
+    remoting_client: &LcuClient,
+    request: ReportRequest,
+) -> Result<ReportQueue, CommandError> {
+    if request.categories.is_empty() {
+        return Err(CommandError::InvalidReport(
+            "pick at least one category".to_string(),
+        ));
+    }
+
+    let state = host.state::<ManagedReportState>();
+    let mut state = state.0.lock().await;
+
+    let queue = state
+        .queue
+        .as_mut()
+        .filter(|queue| queue.game_id == request.game_id)
+        .ok_or_else(|| {
+            CommandError::InvalidReport(format!("game {} is not up for review", request.game_id))
+        })?;
+
+    let player = queue
+        .players
+        .iter_mut()
+        .find(|p| p.puuid == request.puuid)
+        .ok_or_else(|| CommandError::InvalidReport("player was not in this game".to_string()))?;
+
+    if player.reported {
+        return Err(CommandError::InvalidReport(
+            "player was already reported".to_string(),
+        ));
+    }
+
+    let mut categories = request.categories;
+    categories.sort();
+    categories.dedup();
+
+    lcu_response(
+        remoting_client
+            .post(
+                "/lol-player-report-sender/v1/end-of-game-reports".to_string(),
+                serde_json::json!({
+                    "gameId": request.game_id,
+                    "categories": categories,
+                    "offenderSummonerId": player.summoner_id,
+                    "offenderPuuid": player.puuid,
+                    "comment": request.comment,
+                }),
+            )
+            .await,
+    )?;
+
+    player.reported = true;
+    let queue = queue.clone();
+    host.emit("report_queue_update", &queue);
+
+    Ok(queue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn queue_lists_everyone_but_us_without_categories() {
+        let stats = json!({
+            "gameId": 7,
+            "localPlayer": { "summonerId": 1, "puuid": "me" },
+            "teams": [
+                {
+                    "isPlayerTeam": true,
+                    "players": [
+                        { "summonerId": 1, "puuid": "me" },
+                        { "summonerId": 2, "puuid": "friend", "riotIdGameName": "Pal" }
+                    ]
+                },
+                {
+                    "isPlayerTeam": false,
+                    "players": [{ "summonerId": 3, "puuid": "enemy", "championId": 64 }]
+                }
+            ]
+        });
+
+        let stats: EndOfGameStats = serde_json::from_value(stats).unwrap();
+        let queue = report_queue(&stats, &HashSet::from([2]));
+        let players: Vec<_> = queue
+            .players
+            .iter()
+            .map(|p| (p.puuid.as_str(), p.teammate, p.friend, p.reported))
+            .collect();
+
+        assert_eq!(
+            players,
+            vec![
+                ("friend", true, true, false),
+                ("enemy", false, false, false)
+            ]
+        );
+        assert_eq!(queue.players[0].game_name, "Pal");
+        assert_eq!(queue.players[1].champion_id, 64);
+    }
+
+    #[test]
+    fn parses_the_stats_block() {
+        let stats: EndOfGameStats = serde_json::from_value(json!({
+            "gameId": 4567,
+            "gameLength": 1800,
+            "gameMode": "CLASSIC",
+            "queueType": "RANKED_SOLO_5x5",
+            "someNewRiotField": [1, 2, 3],
+            "localPlayer": {
+                "summonerId": 1,
+                "puuid": "me",
+                "championId": 64,
+                "items": [3071, 3047, 0, 0, 0, 0, 3364],
+                "stats": {
+                    "CHAMPIONS_KILLED": 7,
+                    "NUM_DEATHS": 2,
+                    "ASSISTS": 9,
+                    "GOLD_EARNED": 12400,
+                    "TOTAL_DAMAGE_DEALT_TO_CHAMPIONS": 21000,
+                    "WARD_PLACED": 12
+                }
+            },
+            "teams": [
+                { "teamId": 100, "isPlayerTeam": false, "isWinningTeam": true, "players": [] },
+                { "teamId": 200, "isPlayerTeam": true, "isWinningTeam": false, "players": "??" }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(stats.game_id, 4567);
+        assert_eq!(stats.local_player.items[0], 3071);
+        assert_eq!(stats.local_player.stats.kills, 7);
+        assert_eq!(stats.local_player.stats.kda(), 8.0);
+        assert_eq!(stats.local_player.stats.damage_to_champions, 21000);
+        assert_eq!(stats.player_team().unwrap().team_id, 200);
+        assert_eq!(stats.won(), Some(false));
+        assert!(stats.teams[1].players.is_empty());
+
+        let sent = serde_json::to_value(&stats.local_player.stats).unwrap();
+        assert_eq!(sent["goldEarned"], 12400);
+    }
+
+    #[test]
+    fn game_ids_can_be_strings() {
+        let stats: EndOfGameStats = serde_json::from_value(json!({
+            "gameId": "4567",
+            "teams": [{ "teamId": 100, "players": [
+                { "puuid": "a", "items": [3071, "??", 3047] },
+                { "puuid": ["not", "a", "string"] },
+                { "puuid": "b" }
+            ] }]
+        }))
+        .unwrap();
+
+        assert_eq!(stats.game_id, 4567);
+        let players = &stats.teams[0].players;
+        assert_eq!(
+            players.iter().map(|p| p.puuid.as_str()).collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert_eq!(players[0].items, vec![3071, 0, 3047]);
+
+        let stats: EndOfGameStats = serde_json::from_value(json!({ "gameId": null })).unwrap();
+        assert_eq!(stats.game_id, 0);
+    }
+
+    #[test]
+    fn categories_use_the_client_names() {
+        let categories: Vec<ReportCategory> =
+            serde_json::from_value(json!(["LEAVING_AFK", "THIRD_PARTY_TOOLS"])).unwrap();
+        assert_eq!(
+            categories,
+            vec![ReportCategory::LeavingAfk, ReportCategory::ThirdPartyTools]
+        );
+        assert!(serde_json::from_value::<ReportCategory>(json!("ALL")).is_err());
+    }
+}
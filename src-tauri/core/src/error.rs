@@ -0,0 +1,179 @@
+use serde::{de::DeserializeOwned, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+/// Returned from every command so the frontend can tell failures apart.
+///
+/// Serialized as `{ code, message, status }`, `status` being the LCU HTTP status if there was one.
+#[derive(Debug, Clone)]
+pub enum CommandError {
+    /// The League client isn't running or we haven't connected to it yet.
+    NotConnected,
+    /// The command only works during champ select.
+    NotInChampSelect,
+    /// The League client rejected the request or couldn't be reached.
+    Lcu {
+        status: Option<u16>,
+        message: String,
+    },
+    /// The League client answered with something we couldn't parse.
+    InvalidResponse(String),
+    /// Reading or writing local files failed.
+    Io(String),
+    /// No config profile with this name exists.
+    UnknownProfile(String),
+    /// No multi search provider with this id, built-in or from the config.
+    UnknownProvider(String),
+    /// Writing to the clipboard failed.
+    Clipboard(String),
+    /// Reading or writing the match database failed.
+    Database(String),
+    /// A report that wasn't reviewed, or doesn't match the last game.
+    InvalidReport(String),
+}
+
+impl CommandError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommandError::NotConnected => "NOT_CONNECTED",
+            CommandError::NotInChampSelect => "NOT_IN_CHAMP_SELECT",
+            CommandError::Lcu { .. } => "LCU_ERROR",
+            CommandError::InvalidResponse(_) => "INVALID_RESPONSE",
+            CommandError::Io(_) => "IO_ERROR",
+            CommandError::UnknownProfile(_) => "UNKNOWN_PROFILE",
+            CommandError::UnknownProvider(_) => "UNKNOWN_PROVIDER",
+            CommandError::Clipboard(_) => "CLIPBOARD_ERROR",
+            CommandError::Database(_) => "DATABASE_ERROR",
+            CommandError::InvalidReport(_) => "INVALID_REPORT",
+        }
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            CommandError::Lcu { status, .. } => *status,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::NotConnected => write!(f, "Not connected to the League client"),
+            CommandError::NotInChampSelect => write!(f, "Champ select is not active"),
+            CommandError::Lcu {
+                status: Some(status),
+                message,
+            } => write!(f, "League client returned {}: {}", status, message),
+            CommandError::Lcu {
+                status: None,
+                message,
+            } => write!(f, "League client request failed: {}", message),
+            CommandError::InvalidResponse(message) => {
+                write!(f, "Unexpected League client response: {}", message)
+            }
+            CommandError::Io(message) => write!(f, "{}", message),
+            CommandError::UnknownProfile(name) => write!(f, "No profile named {}", name),
+            CommandError::UnknownProvider(id) => {
+                write!(f, "Unknown multi search provider {}", id)
+            }
+            CommandError::Clipboard(message) => write!(f, "Failed to copy: {}", message),
+            CommandError::Database(message) => write!(f, "Match database error: {}", message),
+            CommandError::InvalidReport(message) => write!(f, "Can't send report: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct SerializedError {
+            code: &'static str,
+            message: String,
+            status: Option<u16>,
+        }
+
+        SerializedError {
+            code: self.code(),
+            message: self.to_string(),
+            status: self.status(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(err: std::io::Error) -> Self {
+        CommandError::Io(err.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for CommandError {
+    fn from(err: rusqlite::Error) -> Self {
+        CommandError::Database(err.to_string())
+    }
+}
+
+/// Checks a REST result, the client answers failed requests with
+/// `{ "errorCode", "httpStatus", "message" }` rather than an error.
+pub fn lcu_response<E: fmt::Debug>(response: Result<Value, E>) -> Result<Value, CommandError> {
+    let value = response.map_err(|err| CommandError::Lcu {
+        status: None,
+        message: format!("{err:?}"),
+    })?;
+
+    let status = value
+        .get("httpStatus")
+        .and_then(Value::as_u64)
+        .filter(|status| *status >= 400);
+
+    match status {
+        Some(status) => Err(CommandError::Lcu {
+            status: Some(status as u16),
+            message: value
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        }),
+        None => Ok(value),
+    }
+}
+
+pub fn parse_response<T: DeserializeOwned>(value: Value) -> Result<T, CommandError> {
+    serde_json::from_value(value).map_err(|err| CommandError::InvalidResponse(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn lcu_error_bodies_become_errors() {
+        let body = json!({
+            "errorCode": "RPC_ERROR",
+            "httpStatus": 404,
+            "message": "No active delegate"
+        });
+
+        let err = lcu_response::<()>(Ok(body)).unwrap_err();
+        assert_eq!(err.status(), Some(404));
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            json!({
+                "code": "LCU_ERROR",
+                "message": "League client returned 404: No active delegate",
+                "status": 404
+            })
+        );
+    }
+
+    #[test]
+    fn successful_responses_pass_through() {
+        let body = json!({ "participants": [] });
+        assert_eq!(lcu_response::<()>(Ok(body.clone())).unwrap(), body);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::error::CommandError;
+use serde::Serialize;
+use serde_json::Value;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// What the automations need from the app running them: the shared state registered by
+/// `manage_state` and somewhere to send events and notifications.
+///
+/// Implemented for the Tauri `AppHandle` with the `tauri` feature, and by [`Headless`].
+pub trait Host: Clone + Send + Sync + 'static {
+    /// Panics if `T` was never registered, same as Tauri's `Manager::state`.
+    fn state<T: Send + Sync + 'static>(&self) -> &T;
+
+    /// Sends `event` to the frontend and to anything listening on this side.
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S);
+
+    fn notify(&self, title: &str, body: &str, sound: bool);
+
+    fn copy_text(&self, text: String) -> Result<(), CommandError>;
+
+    /// Where session recordings go, `None` if there is nowhere to put them.
+    fn log_dir(&self) -> Option<PathBuf>;
+}
+
+/// Collects the shared state before the host starts.
+pub trait StateRegistry {
+    fn manage<T: Send + Sync + 'static>(&mut self, state: T);
+}
+
+/// An event sent through a [`Headless`] host.
+#[derive(Debug, Clone)]
+pub struct HostEvent {
+    pub event: String,
+    pub payload: Value,
+}
+
+/// A notification shown through a [`Headless`] host.
+#[derive(Debug, Clone, Serialize)]
+pub struct HostNotification {
+    pub title: String,
+    pub body: String,
+    pub sound: bool,
+}
+
+/// A host without a window, events go to whoever subscribed and notifications are printed.
+#[derive(Clone)]
+pub struct Headless {
+    inner: Arc<HeadlessInner>,
+}
+
+struct HeadlessInner {
+    state: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    events: broadcast::Sender<HostEvent>,
+    log_dir: Option<PathBuf>,
+}
+
+/// Builds a [`Headless`] host, pass it to `manage_state` first.
+#[derive(Default)]
+pub struct HeadlessBuilder {
+    state: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    log_dir: Option<PathBuf>,
+}
+
+impl StateRegistry for HeadlessBuilder {
+    fn manage<T: Send + Sync + 'static>(&mut self, state: T) {
+        self.state.insert(TypeId::of::<T>(), Box::new(state));
+    }
+}
+
+impl HeadlessBuilder {
+    pub fn log_dir(mut self, dir: PathBuf) -> Self {
+        self.log_dir = Some(dir);
+        self
+    }
+
+    pub fn build(self) -> Headless {
+        let (events, _) = broadcast::channel(64);
+        Headless {
+            inner: Arc::new(HeadlessInner {
+                state: self.state,
+                events,
+                log_dir: self.log_dir,
+            }),
+        }
+    }
+}
+
+impl Headless {
+    pub fn builder() -> HeadlessBuilder {
+        HeadlessBuilder::default()
+    }
+
+    /// Events sent from now on, a receiver that falls behind skips the oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<HostEvent> {
+        self.inner.events.subscribe()
+    }
+}
+
+impl Host for Headless {
+    fn state<T: Send + Sync + 'static>(&self) -> &T {
+        self.inner
+            .state
+            .get(&TypeId::of::<T>())
+            .and_then(|state| state.downcast_ref())
+            .unwrap_or_else(|| panic!("{} is not managed", std::any::type_name::<T>()))
+    }
+
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        let payload = serde_json::to_value(payload).unwrap_or_default();
+        // Nobody listening is fine.
+        let _ = self.inner.events.send(HostEvent {
+            event: event.to_string(),
+            payload,
+        });
+    }
+
+    /// Also sent as a `notification` event, so tests and the CLI can see what would have shown.
+    fn notify(&self, title: &str, body: &str, sound: bool) {
+        println!("Notification: {}: {}", title, body);
+        self.emit(
+            "notification",
+            HostNotification {
+                title: title.to_string(),
+                body: body.to_string(),
+                sound,
+            },
+        );
+    }
+
+    fn copy_text(&self, _text: String) -> Result<(), CommandError> {
+        Err(CommandError::Clipboard(
+            "there is no clipboard in headless mode".to_string(),
+        ))
+    }
+
+    fn log_dir(&self) -> Option<PathBuf> {
+        self.inner.log_dir.clone()
+    }
+}
+
+#[cfg(feature = "tauri")]
+mod tauri_host {
+    use super::{Host, StateRegistry};
+    use crate::error::CommandError;
+    use serde::Serialize;
+    use std::path::PathBuf;
+    use tauri::{
+        api::notification::Notification, App, AppHandle, ClipboardManager, Manager, Runtime,
+    };
+
+    /// Played with each notification when `sound` is on, named the way each platform expects.
+    #[cfg(target_os = "windows")]
+    const SOUND: &str = "Default";
+    #[cfg(target_os = "macos")]
+    const SOUND: &str = "NSUserNotificationDefaultSoundName";
+    /// From the freedesktop sound naming spec, played by the notification daemon.
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    const SOUND: &str = "message-new-instant";
+
+    impl<R: Runtime> StateRegistry for App<R> {
+        fn manage<T: Send + Sync + 'static>(&mut self, state: T) {
+            Manager::manage(self, state);
+        }
+    }
+
+    impl<R: Runtime> Host for AppHandle<R> {
+        fn state<T: Send + Sync + 'static>(&self) -> &T {
+            Manager::state::<T>(self).inner()
+        }
+
+        /// `emit_all` alone only reaches the webviews, the tray listens on this side.
+        fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+            let json = serde_json::to_string(&payload).ok();
+            let _ = self.emit_all(event, payload);
+            self.trigger_global(event, json);
+        }
+
+        /// On Linux this goes through the `org.freedesktop.Notifications` service on the
+        /// session bus, so any notification daemon works, including a local one for testing.
+        fn notify(&self, title: &str, body: &str, sound: bool) {
+            let mut notification = Notification::new(&self.config().tauri.bundle.identifier)
+                .title(title)
+                .body(body);
+            if sound {
+                notification = notification.sound(SOUND);
+            }
+
+            if let Err(err) = notification.show() {
+                println!("Failed to show notification: {}", err);
+            }
+        }
+
+        fn copy_text(&self, text: String) -> Result<(), CommandError> {
+            self.clipboard_manager()
+                .write_text(text)
+                .map_err(|err| CommandError::Clipboard(err.to_string()))
+        }
+
+        fn log_dir(&self) -> Option<PathBuf> {
+            self.path_resolver().app_log_dir()
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use anyhow::{anyhow, Context};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use shaco::rest::{LCUClientInfo, RESTClient};
+use shaco::utils::process_info;
+use std::fmt;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    tungstenite::{client::IntoClientRequest, http::HeaderValue, Message},
+    Connector, MaybeTlsStream, WebSocketStream,
+};
+
+const WAMP_SUBSCRIBE: u8 = 5;
+const WAMP_EVENT: u8 = 8;
+
+/// Where the League client connection info comes from.
+#[derive(Debug, Clone)]
+pub enum ConnectionSource {
+    /// Read the command line of the running `LeagueClientUx` process.
+    Process,
+    /// Use the given command line instead, e.g. to point Reveal at a mock LCU.
+    Injected(Vec<String>),
+    /// Replaying a recording, there's no client and requests are never sent.
+    Replay,
+}
+
+impl ConnectionSource {
+    /// `REVEAL_LCU_PORT` and `REVEAL_LCU_TOKEN` override process discovery when both are set.
+    pub fn from_env() -> Self {
+        let port = std::env::var("REVEAL_LCU_PORT")
+            .ok()
+            .and_then(|p| p.parse::<u16>().ok());
+        let token = std::env::var("REVEAL_LCU_TOKEN").ok();
+
+        match (port, token) {
+            (Some(port), Some(token)) => Self::injected(port, &token),
+            _ => Self::Process,
+        }
+    }
+
+    /// Builds the same arguments the League client is launched with, serving both the app and
+    /// the remoting API from a single port.
+    pub fn injected(port: u16, token: &str) -> Self {
+        Self::Injected(vec![
+            format!("--app-port={}", port),
+            format!("--remoting-auth-token={}", token),
+            format!("--riotclient-app-port={}", port),
+            format!("--riotclient-auth-token={}", token),
+        ])
+    }
+
+    pub fn args(&self) -> Option<Vec<String>> {
+        match self {
+            ConnectionSource::Process => process_info::get_league_process_args(),
+            ConnectionSource::Injected(args) => Some(args.clone()),
+            ConnectionSource::Replay => Self::injected(0, "replay").args(),
+        }
+    }
+
+    pub fn connect(&self, args: Vec<String>) -> anyhow::Result<LcuConnection> {
+        match self {
+            ConnectionSource::Replay => LcuConnection::replay(args),
+            _ => LcuConnection::from_args(args),
+        }
+    }
+}
+
+/// Port and token of a client API, which also serves the websocket.
+#[derive(Debug, Clone)]
+pub struct LcuAuth {
+    pub port: u16,
+    pub token: String,
+}
+
+impl LcuAuth {
+    /// The League client API.
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        Self::from_named_args(args, "--app-port=", "--remoting-auth-token=")
+    }
+
+    /// The Riot client API, which serves the chat endpoints.
+    pub fn riot_client_from_args(args: &[String]) -> Option<Self> {
+        Self::from_named_args(args, "--riotclient-app-port=", "--riotclient-auth-token=")
+    }
+
+    fn from_named_args(args: &[String], port: &str, token: &str) -> Option<Self> {
+        let arg = |name: &str| {
+            args.iter()
+                .find_map(|a| a.trim_matches('"').strip_prefix(name).map(str::to_string))
+        };
+
+        Some(Self {
+            port: arg(port)?.parse().ok()?,
+            token: arg(token)?,
+        })
+    }
+
+    fn basic_auth(&self) -> String {
+        format!("Basic {}", STANDARD.encode(format!("riot:{}", self.token)))
+    }
+}
+
+/// A client REST API, or a stand-in that fails every request without sending it.
+#[derive(Clone)]
+pub enum LcuClient {
+    Rest(RESTClient),
+    Replay,
+}
+
+/// A failed request, formatted the same way as the REST client's own error.
+pub struct LcuClientError(String);
+
+impl fmt::Debug for LcuClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl LcuClient {
+    pub async fn get(&self, endpoint: String) -> Result<Value, LcuClientError> {
+        match self {
+            LcuClient::Rest(client) => client.get(endpoint).await.map_err(rest_error),
+            LcuClient::Replay => Err(replay_error(&endpoint)),
+        }
+    }
+
+    pub async fn post(&self, endpoint: String, body: Value) -> Result<Value, LcuClientError> {
+        match self {
+            LcuClient::Rest(client) => client.post(endpoint, body).await.map_err(rest_error),
+            LcuClient::Replay => Err(replay_error(&endpoint)),
+        }
+    }
+
+    pub async fn patch(&self, endpoint: String, body: Value) -> Result<Value, LcuClientError> {
+        match self {
+            LcuClient::Rest(client) => client.patch(endpoint, body).await.map_err(rest_error),
+            LcuClient::Replay => Err(replay_error(&endpoint)),
+        }
+    }
+}
+
+fn rest_error<E: fmt::Debug>(err: E) -> LcuClientError {
+    LcuClientError(format!("{err:?}"))
+}
+
+fn replay_error(endpoint: &str) -> LcuClientError {
+    LcuClientError(format!("{} not sent while replaying", endpoint))
+}
+
+/// The REST clients of one connection, cheap to clone.
+#[derive(Clone)]
+pub struct LcuClients {
+    pub app: LcuClient,
+    pub remoting: LcuClient,
+}
+
+/// Everything needed to talk to one running client.
+pub struct LcuConnection {
+    pub info: LCUClientInfo,
+    pub auth: LcuAuth,
+    pub riot_auth: Option<LcuAuth>,
+    pub app_client: LcuClient,
+    pub remoting_client: LcuClient,
+}
+
+impl LcuConnection {
+    pub fn from_args(args: Vec<String>) -> anyhow::Result<Self> {
+        let auth = LcuAuth::from_args(&args).context("missing --app-port or auth token")?;
+        let riot_auth = LcuAuth::riot_client_from_args(&args);
+        let info = process_info::get_auth_info(args).map_err(|err| anyhow!("{err:?}"))?;
+        let app_client = RESTClient::new(info.clone(), false).map_err(|err| anyhow!("{err:?}"))?;
+        let remoting_client =
+            RESTClient::new(info.clone(), true).map_err(|err| anyhow!("{err:?}"))?;
+
+        Ok(Self {
+            info,
+            auth,
+            riot_auth,
+            app_client: LcuClient::Rest(app_client),
+            remoting_client: LcuClient::Rest(remoting_client),
+        })
+    }
+
+    /// Replayed events still drive the handlers, the requests they make fail right away.
+    fn replay(args: Vec<String>) -> anyhow::Result<Self> {
+        let auth = LcuAuth::from_args(&args).context("missing --app-port or auth token")?;
+        let info = process_info::get_auth_info(args).map_err(|err| anyhow!("{err:?}"))?;
+
+        Ok(Self {
+            info,
+            auth,
+            riot_auth: None,
+            app_client: LcuClient::Replay,
+            remoting_client: LcuClient::Replay,
+        })
+    }
+
+    pub fn clients(&self) -> LcuClients {
+        LcuClients {
+            app: self.app_client.clone(),
+            remoting: self.remoting_client.clone(),
+        }
+    }
+}
+
+/// A single `OnJsonApiEvent` published by the client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LcuEvent {
+    pub subscription: String,
+    pub uri: String,
+    pub event_type: String,
+    pub data: Value,
+}
+
+impl LcuEvent {
+    /// Wraps a REST response so initial state can be recorded like any other event.
+    pub fn snapshot(endpoint: &str, data: Value) -> Self {
+        Self {
+            subscription: subscription_name(endpoint),
+            uri: endpoint.to_string(),
+            event_type: "Snapshot".to_string(),
+            data,
+        }
+    }
+}
+
+/// `/lol-gameflow/v1/gameflow-phase` -> `OnJsonApiEvent_lol-gameflow_v1_gameflow-phase`
+pub fn subscription_name(endpoint: &str) -> String {
+    format!("OnJsonApiEvent{}", endpoint.replace('/', "_"))
+}
+
+pub struct LcuWebsocket {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl LcuWebsocket {
+    pub async fn connect(auth: &LcuAuth) -> anyhow::Result<Self> {
+        let mut request = format!("wss://127.0.0.1:{}", auth.port).into_client_request()?;
+        request
+            .headers_mut()
+            .insert("Authorization", HeaderValue::from_str(&auth.basic_auth())?);
+
+        // The client serves a self signed certificate on localhost.
+        let tls = native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .build()?;
+
+        let (stream, _) = tokio_tungstenite::connect_async_tls_with_config(
+            request,
+            None,
+            Some(Connector::NativeTls(tls)),
+        )
+        .await
+        .context("failed to connect to LCU websocket")?;
+
+        Ok(Self { stream })
+    }
+
+    pub async fn subscribe(&mut self, endpoint: &str) -> anyhow::Result<()> {
+        self.send_wamp(WAMP_SUBSCRIBE, endpoint).await
+    }
+
+    async fn send_wamp(&mut self, opcode: u8, endpoint: &str) -> anyhow::Result<()> {
+        let frame = serde_json::json!([opcode, subscription_name(endpoint)]);
+        self.stream
+            .send(Message::Text(frame.to_string()))
+            .await
+            .map_err(|err| anyhow!("failed to send {}: {err}", endpoint))
+    }
+
+    /// Waits for the next event, returns `None` once the socket is closed.
+    pub async fn next(&mut self) -> Option<LcuEvent> {
+        while let Some(msg) = self.stream.next().await {
+            let text = match msg {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) | Err(_) => return None,
+                Ok(_) => continue,
+            };
+
+            if let Some(event) = parse_event(&text) {
+                return Some(event);
+            }
+        }
+
+        None
+    }
+}
+
+/// Parses a WAMP event frame, `[8, "<subscription>", { "data", "eventType", "uri" }]`.
+pub fn parse_event(text: &str) -> Option<LcuEvent> {
+    let frame: (u8, String, Value) = serde_json::from_str(text).ok()?;
+    if frame.0 != WAMP_EVENT {
+        return None;
+    }
+
+    let (_, subscription, mut payload) = frame;
+    Some(LcuEvent {
+        subscription,
+        uri: payload["uri"].as_str().unwrap_or_default().to_string(),
+        event_type: payload["eventType"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        data: payload["data"].take(),
+    })
+}
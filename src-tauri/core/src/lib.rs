@@ -0,0 +1,256 @@
+//! The League client automations shared by the tray app and `reveal-cli`: the connection
+//! loop, the gameflow phase handlers and the config. Everything runs against a [`Host`], which
+//! holds the shared state and forwards events to whatever frontend is attached.
+
+pub mod champ_select;
+pub mod config;
+pub mod connection;
+pub mod dodge;
+pub mod dodge_history;
+pub mod end_game;
+pub mod error;
+pub mod host;
+pub mod lcu;
+pub mod lobby;
+pub mod match_db;
+#[cfg(test)]
+mod mock_lcu;
+pub mod multisearch;
+pub mod notifications;
+pub mod ready_check;
+pub mod recording;
+pub mod region;
+pub mod state;
+pub mod team_export;
+pub mod utils;
+
+pub use crate::host::{Headless, Host, StateRegistry};
+
+use crate::champ_select::{AutoPickConfig, AutoPickState, ChampSelectSession, TeamTracker};
+use crate::config::ConfigFile;
+use crate::connection::ConnectionState;
+use crate::dodge::ScheduledDodge;
+use crate::end_game::{EndOfGameStats, ReportQueue};
+use crate::error::CommandError;
+use crate::lcu::{LcuClient, LcuClients, LcuEvent};
+use crate::lobby::Lobby;
+use crate::match_db::MatchDb;
+use crate::multisearch::TemplateProvider;
+use crate::notifications::NotificationConfig;
+use crate::ready_check::{AcceptRules, ReadyCheckState};
+use crate::state::{GameflowPhase, GameflowStateMachine};
+use crate::team_export::TeamFormat;
+use serde::{Deserialize, Serialize};
+use shaco::rest::LCUClientInfo;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/* ───────────────────────────────────────────────────────────────
+   Shared Global State Wrappers
+───────────────────────────────────────────────────────────────*/
+
+pub struct LCU(pub Mutex<LCUState>);
+
+pub struct LCUState {
+    pub connected: bool,
+    /// Where the connection supervisor is at, `connected` is only true once it's `Connected`.
+    pub connection_state: ConnectionState,
+    pub data: Option<LCUClientInfo>,
+    /// Owned by the connection supervisor, replaced as a whole on every reconnect.
+    pub clients: Option<LcuClients>,
+}
+
+impl LCUState {
+    pub fn connected_clients(&self) -> Result<LcuClients, CommandError> {
+        self.clients.clone().ok_or(CommandError::NotConnected)
+    }
+}
+
+pub struct ManagedDodgeState(pub Mutex<DodgeState>);
+
+pub struct DodgeState {
+    pub last_dodge: Option<u64>,
+    pub enabled: Option<u64>,
+    pub scheduled: Option<ScheduledDodge>,
+}
+
+pub struct ManagedReportState(pub Mutex<ReportState>);
+pub struct ReportState {
+    pub last_report: Option<u64>,
+    /// Players from the last game waiting for the user to review them.
+    pub queue: Option<ReportQueue>,
+    pub last_game: Option<EndOfGameStats>,
+}
+
+pub struct ManagedGameflowState(pub Mutex<GameflowStateMachine>);
+
+pub struct ManagedAutoPickState(pub Mutex<AutoPickState>);
+
+pub struct ManagedTeamTracker(pub Mutex<TeamTracker>);
+
+/// Behind a blocking mutex, only ever locked on the blocking pool through `query_db`.
+pub struct ManagedMatchDb(pub Arc<std::sync::Mutex<MatchDb>>);
+
+pub struct ManagedReadyCheckState(pub Mutex<ReadyCheckState>);
+
+/// The active profile's settings.
+pub struct AppConfig(pub Mutex<Config>);
+
+/// Every profile, saved back to `config.json` whenever one changes.
+pub struct ConfigProfiles(pub Mutex<ConfigFile>);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    pub auto_open: bool,
+    pub auto_accept: bool,
+    pub accept_delay: u32,
+    /// Which ready checks `auto_accept` accepts, declines or leaves to the user.
+    #[serde(default)]
+    pub accept_rules: AcceptRules,
+    #[serde(default = "default_provider")]
+    pub multi_provider: String,
+    /// User-defined multi search sites, selectable as `multi_provider` by id.
+    #[serde(default)]
+    pub custom_providers: Vec<TemplateProvider>,
+    /// Copy the team once champ select starts, an alternative to `auto_open` that doesn't
+    /// steal focus.
+    #[serde(default)]
+    pub auto_copy: bool,
+    #[serde(default)]
+    pub team_format: TeamFormat,
+    /// List the players after each game so the user can pick who to report and for what.
+    #[serde(default)]
+    pub report_review: bool,
+    #[serde(default)]
+    pub record_sessions: bool,
+    #[serde(default)]
+    pub auto_pick: AutoPickConfig,
+    /// How many ms before finalization ends the last second dodge is sent.
+    #[serde(default = "default_dodge_lead_time")]
+    pub dodge_lead_time: u64,
+    /// Desktop notifications for events worth switching to the client for.
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            auto_open: true,
+            auto_accept: true,
+            accept_delay: 2000,
+            accept_rules: AcceptRules::default(),
+            multi_provider: default_provider(),
+            custom_providers: Vec::new(),
+            auto_copy: false,
+            team_format: TeamFormat::default(),
+            report_review: false,
+            record_sessions: false,
+            auto_pick: AutoPickConfig::default(),
+            dodge_lead_time: default_dodge_lead_time(),
+            notifications: NotificationConfig::default(),
+        }
+    }
+}
+
+fn default_provider() -> String {
+    "opgg".to_string()
+}
+
+fn default_dodge_lead_time() -> u64 {
+    500
+}
+
+/// Registers every piece of shared state with the Tauri app or a `Headless` host.
+pub fn manage_state(manager: &mut impl StateRegistry, profiles: ConfigFile, match_db: MatchDb) {
+    manager.manage(LCU(Mutex::new(LCUState {
+        connected: false,
+        connection_state: ConnectionState::default(),
+        data: None,
+        clients: None,
+    })));
+    manager.manage(ManagedDodgeState(Mutex::new(DodgeState {
+        last_dodge: None,
+        enabled: None,
+        scheduled: None,
+    })));
+    manager.manage(ManagedReportState(Mutex::new(ReportState {
+        last_report: None,
+        queue: None,
+        last_game: None,
+    })));
+    manager.manage(ManagedGameflowState(
+        Mutex::new(GameflowStateMachine::new()),
+    ));
+    manager.manage(ManagedAutoPickState(Mutex::new(AutoPickState::default())));
+    manager.manage(ManagedTeamTracker(Mutex::new(TeamTracker::default())));
+    manager.manage(ManagedMatchDb(Arc::new(std::sync::Mutex::new(match_db))));
+    manager.manage(ManagedReadyCheckState(Mutex::new(
+        ReadyCheckState::default(),
+    )));
+    manager.manage(AppConfig(Mutex::new(profiles.active_config())));
+    manager.manage(ConfigProfiles(Mutex::new(profiles)));
+}
+
+/* ───────────────────────────────────────────────────────────────
+   Websocket Message Routing
+───────────────────────────────────────────────────────────────*/
+
+async fn handle_ws_message<H: Host>(
+    msg: LcuEvent,
+    host: &H,
+    remoting_client: &LcuClient,
+    app_client: &LcuClient,
+) {
+    match msg.subscription.as_str() {
+        "OnJsonApiEvent_lol-gameflow_v1_gameflow-phase" => {
+            let client_state = serde_json::from_value::<GameflowPhase>(msg.data.clone())
+                .unwrap_or(GameflowPhase::None);
+            state::handle_client_state(client_state, host, remoting_client, app_client).await;
+        }
+
+        "OnJsonApiEvent_lol-champ-select_v1_session" => {
+            // Sent with no data when champ select ends.
+            if msg.event_type == "Delete" {
+                return;
+            }
+
+            let champ_select = match serde_json::from_value::<ChampSelectSession>(msg.data) {
+                Ok(champ_select) => champ_select,
+                Err(err) => {
+                    println!("Failed to parse champ select session: {}", err);
+                    return;
+                }
+            };
+
+            champ_select::record_champ_select_session(&champ_select, host, remoting_client).await;
+            champ_select::notify_pick_turn(&champ_select, host).await;
+            champ_select::handle_champ_select_actions(&champ_select, host, remoting_client).await;
+
+            if champ_select.timer.phase == "FINALIZATION" {
+                dodge::schedule_dodge(&champ_select, host, remoting_client).await;
+            }
+        }
+
+        "OnJsonApiEvent_lol-matchmaking_v1_ready-check" => {
+            ready_check::handle_ready_check_update(msg.data, host).await;
+        }
+
+        "OnJsonApiEvent_chat_v5_participants" => {
+            let team = match serde_json::from_value::<Lobby>(msg.data) {
+                Ok(team) => team.champ_select_team(),
+                Err(err) => {
+                    println!("Failed to parse chat participants: {}", err);
+                    return;
+                }
+            };
+
+            champ_select::handle_team_update(team, host).await;
+        }
+
+        _ => {
+            println!("Unhandled Message Type: {}", msg.subscription);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::error::{lcu_response, parse_response, CommandError};
+use crate::lcu::LcuClient;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Participant {
+    pub cid: String,
+    pub game_name: String,
+    pub game_tag: String,
+    pub muted: bool,
+    pub name: String,
+    pub pid: String,
+    pub puuid: String,
+    pub region: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Lobby {
+    pub participants: Vec<Participant>,
+}
+
+impl Lobby {
+    /// Drops everyone outside the champ select chat room, whose cids contain `champ-select`.
+    pub fn champ_select_team(self) -> Lobby {
+        Lobby {
+            participants: self
+                .participants
+                .into_iter()
+                .filter(|p| p.cid.contains("champ-select"))
+                .collect(),
+        }
+    }
+}
+
+pub async fn get_lobby_info(app_client: &LcuClient) -> Result<Lobby, CommandError> {
+    let team: Lobby = parse_response(lcu_response(
+        app_client.get("/chat/v5/participants".to_string()).await,
+    )?)?;
+
+    Ok(team.champ_select_team())
+}
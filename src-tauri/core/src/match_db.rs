@@ -0,0 +1,550 @@
+use crate::{
+    champ_select::ChampSelectSession,
+    dodge_history::{DodgeKind, DodgeRecord},
+    end_game::EndOfGameStats,
+    error::CommandError,
+    lobby::Participant,
+    Host, ManagedMatchDb,
+};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::PoisonError;
+
+const V1_INITIAL: &str = "
+    CREATE TABLE games (
+        game_id     INTEGER PRIMARY KEY,
+        queue_id    INTEGER,
+        puuid       TEXT,
+        champion_id INTEGER,
+        started_at  INTEGER NOT NULL,
+        won         INTEGER,
+        stats       TEXT
+    );
+    CREATE TABLE teammates (
+        game_id   INTEGER NOT NULL REFERENCES games (game_id),
+        puuid     TEXT NOT NULL,
+        game_name TEXT NOT NULL,
+        game_tag  TEXT NOT NULL,
+        PRIMARY KEY (game_id, puuid)
+    );
+    CREATE INDEX teammates_puuid ON teammates (puuid);
+    CREATE TABLE dodges (
+        game_id   INTEGER NOT NULL,
+        queue_id  INTEGER,
+        timestamp INTEGER NOT NULL,
+        kind      TEXT NOT NULL
+    );";
+
+/// Schema changes, `MIGRATIONS[n]` takes a database from `user_version` n to n + 1.
+const MIGRATIONS: &[&str] = &[V1_INITIAL];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeammateRecord {
+    pub puuid: String,
+    pub game_name: String,
+    pub game_tag: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameRecord {
+    pub game_id: u64,
+    pub queue_id: Option<i64>,
+    /// Our pick, `None` until we hovered or locked something.
+    pub champion_id: Option<i64>,
+    /// Milliseconds since the unix epoch, when champ select started.
+    pub started_at: u64,
+    /// `None` until the stats block came in, so also for dodged games.
+    pub won: Option<bool>,
+    pub dodged: bool,
+    pub teammates: Vec<TeammateRecord>,
+}
+
+/// Someone we were in champ select with, across every recorded game.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeammateEncounter {
+    pub puuid: String,
+    /// The Riot ID from the latest game, it can change between games.
+    pub game_name: String,
+    pub game_tag: String,
+    pub games: u32,
+    /// Games we won together, out of those with a result.
+    pub wins: u32,
+    pub losses: u32,
+    pub last_seen: u64,
+}
+
+/// Our history with a champ select teammate, sent along with `champ_select_started`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayedWith {
+    /// Games we were on the same team, dodged ones don't count.
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    /// `None` while none of those games has a result.
+    pub win_rate: Option<f64>,
+    pub last_seen: u64,
+}
+
+/// Champ selects, results and dodges, kept in `matches.db` in the app data directory.
+pub struct MatchDb {
+    conn: Connection,
+}
+
+impl MatchDb {
+    pub fn open(path: &Path) -> Result<Self, CommandError> {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Used when the file can't be opened, and by tests.
+    pub fn open_in_memory() -> Result<Self, CommandError> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, CommandError> {
+        let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", i + 1)?;
+            tx.commit()?;
+        }
+
+        Ok(Self { conn })
+    }
+
+    /// Adds the game on its first session update, later updates only fill in our pick.
+    pub fn record_champ_select(
+        &self,
+        session: &ChampSelectSession,
+        queue_id: Option<i64>,
+        timestamp: u64,
+    ) -> Result<(), CommandError> {
+        let local_player = session.local_player();
+        let puuid = local_player
+            .map(|p| p.puuid.as_str())
+            .filter(|p| !p.is_empty());
+        let champion_id = local_player.map(|p| p.champion_id).filter(|id| *id > 0);
+
+        self.conn.execute(
+            "INSERT INTO games (game_id, queue_id, puuid, champion_id, started_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (game_id) DO UPDATE SET
+                queue_id = COALESCE(queue_id, excluded.queue_id),
+                puuid = COALESCE(puuid, excluded.puuid),
+                champion_id = COALESCE(excluded.champion_id, champion_id)",
+            params![session.game_id, queue_id, puuid, champion_id, timestamp],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn record_teammates(
+        &self,
+        game_id: u64,
+        participants: &[Participant],
+    ) -> Result<(), CommandError> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR REPLACE INTO teammates (game_id, puuid, game_name, game_tag)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for p in participants.iter().filter(|p| !p.puuid.is_empty()) {
+                insert.execute(params![game_id, p.puuid, p.game_name, p.game_tag])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Stores the raw stats block with the result, adding the game if champ select wasn't seen.
+    pub fn record_end_of_game(
+        &self,
+        stats: &EndOfGameStats,
+        raw: &Value,
+        timestamp: u64,
+    ) -> Result<(), CommandError> {
+        let champion_id = Some(stats.local_player.champion_id).filter(|id| *id > 0);
+
+        self.conn.execute(
+            "INSERT INTO games (game_id, queue_id, champion_id, started_at, won, stats)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (game_id) DO UPDATE SET
+                queue_id = COALESCE(queue_id, excluded.queue_id),
+                champion_id = COALESCE(excluded.champion_id, champion_id),
+                won = excluded.won,
+                stats = excluded.stats",
+            params![
+                stats.game_id,
+                stats.queue_id,
+                champion_id,
+                timestamp,
+                stats.won(),
+                raw.to_string()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn record_dodge(&self, record: &DodgeRecord) -> Result<(), CommandError> {
+        let kind = match record.kind {
+            DodgeKind::Manual => "manual",
+            DodgeKind::Scheduled => "scheduled",
+        };
+
+        self.conn.execute(
+            "INSERT INTO dodges (game_id, queue_id, timestamp, kind) VALUES (?1, ?2, ?3, ?4)",
+            params![record.game_id, record.queue_id, record.timestamp, kind],
+        )?;
+
+        Ok(())
+    }
+
+    /// Timestamps of the dodges after `since`, oldest first.
+    pub fn dodge_times_since(&self, since: u64) -> Result<Vec<u64>, CommandError> {
+        let times = self
+            .conn
+            .prepare("SELECT timestamp FROM dodges WHERE timestamp > ?1 ORDER BY timestamp")?
+            .query_map([since], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(times)
+    }
+
+    /// The latest games first, with everyone who was in champ select except us.
+    pub fn recent_games(&self, limit: u32) -> Result<Vec<GameRecord>, CommandError> {
+        let mut games = self
+            .conn
+            .prepare(
+                "SELECT g.game_id, g.queue_id, g.champion_id, g.started_at, g.won,
+                        EXISTS (SELECT 1 FROM dodges d WHERE d.game_id = g.game_id)
+                 FROM games g
+                 ORDER BY g.started_at DESC
+                 LIMIT ?1",
+            )?
+            .query_map([limit], |row| {
+                Ok(GameRecord {
+                    game_id: row.get(0)?,
+                    queue_id: row.get(1)?,
+                    champion_id: row.get(2)?,
+                    started_at: row.get(3)?,
+                    won: row.get(4)?,
+                    dodged: row.get(5)?,
+                    teammates: Vec::new(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut teammates = self.conn.prepare(
+            "SELECT t.puuid, t.game_name, t.game_tag
+             FROM teammates t JOIN games g ON g.game_id = t.game_id
+             WHERE t.game_id = ?1 AND t.puuid IS NOT g.puuid",
+        )?;
+        for game in games.iter_mut() {
+            game.teammates = teammates
+                .query_map([game.game_id], |row| {
+                    Ok(TeammateRecord {
+                        puuid: row.get(0)?,
+                        game_name: row.get(1)?,
+                        game_tag: row.get(2)?,
+                    })
+                })?
+                .collect::<Result<_, _>>()?;
+        }
+
+        Ok(games)
+    }
+
+    /// Teammates we played more than once, most frequent first. Dodged lobbies are left out.
+    pub fn teammate_encounters(&self, limit: u32) -> Result<Vec<TeammateEncounter>, CommandError> {
+        let encounters = self
+            .conn
+            .prepare(
+                "SELECT t.puuid,
+                        (SELECT t2.game_name || '#' || t2.game_tag FROM teammates t2
+                         JOIN games g2 ON g2.game_id = t2.game_id
+                         WHERE t2.puuid = t.puuid ORDER BY g2.started_at DESC LIMIT 1),
+                        COUNT(*),
+                        COUNT(CASE WHEN g.won = 1 THEN 1 END),
+                        COUNT(CASE WHEN g.won = 0 THEN 1 END),
+                        MAX(g.started_at)
+                 FROM teammates t JOIN games g ON g.game_id = t.game_id
+                 WHERE t.puuid IS NOT g.puuid
+                    AND NOT EXISTS (SELECT 1 FROM dodges d WHERE d.game_id = g.game_id)
+                 GROUP BY t.puuid
+                 HAVING COUNT(*) > 1
+                 ORDER BY COUNT(*) DESC, MAX(g.started_at) DESC
+                 LIMIT ?1",
+            )?
+            .query_map([limit], |row| {
+                let riot_id: String = row.get(1)?;
+                let (game_name, game_tag) = riot_id.rsplit_once('#').unwrap_or((&riot_id, ""));
+                Ok(TeammateEncounter {
+                    puuid: row.get(0)?,
+                    game_name: game_name.to_string(),
+                    game_tag: game_tag.to_string(),
+                    games: row.get(2)?,
+                    wins: row.get(3)?,
+                    losses: row.get(4)?,
+                    last_seen: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(encounters)
+    }
+
+    /// History with each of `participants` we played with before, leaving out `current_game`.
+    pub fn played_with(
+        &self,
+        participants: &[Participant],
+        current_game: Option<u64>,
+    ) -> Result<HashMap<String, PlayedWith>, CommandError> {
+        let mut query = self.conn.prepare(
+            "SELECT COUNT(*),
+                    COUNT(CASE WHEN g.won = 1 THEN 1 END),
+                    COUNT(CASE WHEN g.won = 0 THEN 1 END),
+                    MAX(g.started_at)
+             FROM teammates t JOIN games g ON g.game_id = t.game_id
+             WHERE t.puuid = ?1 AND t.puuid IS NOT g.puuid AND g.game_id IS NOT ?2
+                AND NOT EXISTS (SELECT 1 FROM dodges d WHERE d.game_id = g.game_id)",
+        )?;
+
+        let mut history = HashMap::new();
+        for p in participants.iter().filter(|p| !p.puuid.is_empty()) {
+            let (games, wins, losses, last_seen): (u32, u32, u32, Option<u64>) = query
+                .query_row(params![p.puuid, current_game], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })?;
+
+            let Some(last_seen) = last_seen.filter(|_| games > 0) else {
+                continue;
+            };
+
+            let decided = wins + losses;
+            history.insert(
+                p.puuid.clone(),
+                PlayedWith {
+                    games,
+                    wins,
+                    losses,
+                    win_rate: (decided > 0).then(|| wins as f64 / decided as f64),
+                    last_seen,
+                },
+            );
+        }
+
+        Ok(history)
+    }
+}
+
+/// Runs `f` against the database on the blocking pool, rusqlite calls would stall the runtime.
+pub async fn query_db<H: Host, T: Send + 'static>(
+    host: &H,
+    f: impl FnOnce(&MatchDb) -> Result<T, CommandError> + Send + 'static,
+) -> Result<T, CommandError> {
+    let db = host.state::<ManagedMatchDb>().0.clone();
+    tokio::task::spawn_blocking(move || {
+        let db = db.lock().unwrap_or_else(PoisonError::into_inner);
+        f(&db)
+    })
+    .await
+    .map_err(|err| CommandError::Database(err.to_string()))?
+}
+
+/// Like [`query_db`], logging failures since recording never blocks the app.
+pub async fn with_db<H: Host>(
+    host: &H,
+    what: &str,
+    f: impl FnOnce(&MatchDb) -> Result<(), CommandError> + Send + 'static,
+) {
+    if let Err(err) = query_db(host, f).await {
+        println!("Failed to record {}: {}", what, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn participant(puuid: &str, game_name: &str) -> Participant {
+        Participant {
+            cid: "champ-select".to_string(),
+            game_name: game_name.to_string(),
+            game_tag: "EUW".to_string(),
+            muted: false,
+            name: game_name.to_string(),
+            pid: String::new(),
+            puuid: puuid.to_string(),
+            region: String::new(),
+        }
+    }
+
+    fn session(game_id: u64, champion_id: i64) -> ChampSelectSession {
+        serde_json::from_value(json!({
+            "gameId": game_id,
+            "localPlayerCellId": 0,
+            "myTeam": [{ "cellId": 0, "championId": champion_id, "puuid": "me" }]
+        }))
+        .unwrap()
+    }
+
+    fn play(db: &MatchDb, game_id: u64, won: bool, teammates: &[(&str, &str)]) {
+        db.record_champ_select(&session(game_id, 0), Some(420), game_id * 1000)
+            .unwrap();
+        db.record_champ_select(&session(game_id, 64), None, game_id * 1000)
+            .unwrap();
+
+        let mut team = vec![participant("me", "Me")];
+        team.extend(
+            teammates
+                .iter()
+                .map(|(puuid, name)| participant(puuid, name)),
+        );
+        db.record_teammates(game_id, &team).unwrap();
+
+        let raw = json!({
+            "gameId": game_id,
+            "teams": [{ "isPlayerTeam": true, "isWinningTeam": won }]
+        });
+        let stats = serde_json::from_value(raw.clone()).unwrap();
+        db.record_end_of_game(&stats, &raw, 0).unwrap();
+    }
+
+    #[test]
+    fn records_games_with_teammates_and_results() {
+        let db = MatchDb::open_in_memory().unwrap();
+        play(&db, 1, true, &[("a", "Alice")]);
+        play(&db, 2, false, &[("a", "Alice"), ("b", "Bob")]);
+        db.record_champ_select(&session(3, 0), Some(420), 3000)
+            .unwrap();
+        db.record_dodge(&DodgeRecord {
+            game_id: 3,
+            queue_id: Some(420),
+            timestamp: 3500,
+            kind: DodgeKind::Manual,
+        })
+        .unwrap();
+
+        let games = db.recent_games(10).unwrap();
+        assert_eq!(
+            games.iter().map(|g| g.game_id).collect::<Vec<_>>(),
+            vec![3, 2, 1]
+        );
+        assert!(games[0].dodged);
+        assert_eq!(games[0].won, None);
+        assert_eq!(games[1].won, Some(false));
+        assert_eq!(games[1].queue_id, Some(420));
+        assert_eq!(games[1].champion_id, Some(64));
+        assert_eq!(games[1].teammates.len(), 2);
+        assert!(games[1].teammates.iter().all(|t| t.puuid != "me"));
+
+        assert_eq!(db.recent_games(1).unwrap().len(), 1);
+        assert_eq!(db.dodge_times_since(3000).unwrap(), vec![3500]);
+        assert!(db.dodge_times_since(3500).unwrap().is_empty());
+    }
+
+    #[test]
+    fn counts_repeat_teammates() {
+        let db = MatchDb::open_in_memory().unwrap();
+        play(&db, 1, true, &[("a", "Alice"), ("b", "Bob")]);
+        play(&db, 2, false, &[("a", "Alice Renamed")]);
+        play(&db, 3, true, &[("a", "Alice Renamed")]);
+
+        // Dodged lobbies don't count, or Bob would show up twice.
+        db.record_champ_select(&session(4, 0), Some(420), 4000)
+            .unwrap();
+        db.record_teammates(4, &[participant("me", "Me"), participant("b", "Bob")])
+            .unwrap();
+        db.record_dodge(&DodgeRecord {
+            game_id: 4,
+            queue_id: Some(420),
+            timestamp: 4500,
+            kind: DodgeKind::Manual,
+        })
+        .unwrap();
+
+        let encounters = db.teammate_encounters(10).unwrap();
+        assert_eq!(encounters.len(), 1);
+        assert_eq!(encounters[0].puuid, "a");
+        assert_eq!(encounters[0].game_name, "Alice Renamed");
+        assert_eq!(encounters[0].game_tag, "EUW");
+        assert_eq!(
+            (
+                encounters[0].games,
+                encounters[0].wins,
+                encounters[0].losses
+            ),
+            (3, 2, 1)
+        );
+        assert_eq!(encounters[0].last_seen, 3000);
+    }
+
+    #[test]
+    fn looks_up_history_for_the_current_team() {
+        let db = MatchDb::open_in_memory().unwrap();
+        play(&db, 1, true, &[("a", "Alice")]);
+        play(&db, 2, false, &[("a", "Alice"), ("b", "Bob")]);
+        play(&db, 3, true, &[("a", "Alice"), ("b", "Bob")]);
+        db.record_dodge(&DodgeRecord {
+            game_id: 3,
+            queue_id: Some(420),
+            timestamp: 3500,
+            kind: DodgeKind::Scheduled,
+        })
+        .unwrap();
+
+        // Game 4 is the one in champ select right now.
+        db.record_champ_select(&session(4, 0), Some(420), 4000)
+            .unwrap();
+        let team = [
+            participant("me", "Me"),
+            participant("a", "Alice"),
+            participant("b", "Bob"),
+            participant("c", "Carol"),
+        ];
+        db.record_teammates(4, &team).unwrap();
+
+        let history = db.played_with(&team, Some(4)).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history["a"],
+            PlayedWith {
+                games: 2,
+                wins: 1,
+                losses: 1,
+                win_rate: Some(0.5),
+                last_seen: 2000,
+            }
+        );
+        assert_eq!(history["b"].games, 1);
+        assert_eq!(history["b"].win_rate, Some(0.0));
+    }
+
+    #[test]
+    fn reopening_keeps_the_schema() {
+        let path = std::env::temp_dir().join(format!("reveal-matches-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let db = MatchDb::open(&path).unwrap();
+        db.record_champ_select(&session(1, 0), None, 0).unwrap();
+        drop(db);
+
+        let db = MatchDb::open(&path).unwrap();
+        assert_eq!(db.recent_games(10).unwrap().len(), 1);
+
+        drop(db);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,959 @@
+use crate::lcu::{subscription_name, ConnectionSource};
+use axum::{
+    body::Bytes,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::{HeaderMap, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    net::{SocketAddr, TcpListener},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::{broadcast, Mutex};
+
+/// A request the app made against the mock.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub body: Value,
+}
+
+struct MockState {
+    token: String,
+    responses: Mutex<HashMap<String, Value>>,
+    requests: Mutex<Vec<RecordedRequest>>,
+    subscriptions: Mutex<HashSet<String>>,
+    events: broadcast::Sender<(String, String)>,
+}
+
+/// A local stand-in for the League client, serving scripted REST responses and WAMP events
+/// over HTTPS on a random port.
+pub struct MockLcu {
+    pub port: u16,
+    pub token: String,
+    state: Arc<MockState>,
+    handle: axum_server::Handle,
+}
+
+impl MockLcu {
+    pub async fn start() -> anyhow::Result<Self> {
+        let token = "mock-token".to_string();
+        let (events, _) = broadcast::channel(64);
+
+        let state = Arc::new(MockState {
+            token: token.clone(),
+            responses: Mutex::new(default_responses()),
+            requests: Mutex::new(Vec::new()),
+            subscriptions: Mutex::new(HashSet::new()),
+            events,
+        });
+
+        let cert = rcgen::generate_simple_self_signed(vec![
+            "127.0.0.1".to_string(),
+            "localhost".to_string(),
+        ])?;
+        let tls = RustlsConfig::from_pem(
+            cert.serialize_pem()?.into_bytes(),
+            cert.serialize_private_key_pem().into_bytes(),
+        )
+        .await?;
+
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+
+        let app = Router::new()
+            .route("/", get(serve_websocket))
+            .fallback(serve_rest)
+            .with_state(state.clone());
+
+        let handle = axum_server::Handle::new();
+        let server = axum_server::from_tcp_rustls(listener, tls)
+            .handle(handle.clone())
+            .serve(app.into_make_service());
+        tokio::spawn(server);
+
+        Ok(Self {
+            port,
+            token,
+            state,
+            handle,
+        })
+    }
+
+    pub fn connection_source(&self) -> ConnectionSource {
+        ConnectionSource::injected(self.port, &self.token)
+    }
+
+    /// Replaces the scripted response for `path`, query string excluded.
+    pub async fn set_response(&self, path: &str, value: Value) {
+        self.state
+            .responses
+            .lock()
+            .await
+            .insert(path.to_string(), value);
+    }
+
+    /// Updates the scripted response and publishes the matching `Update` event.
+    pub async fn update(&self, endpoint: &str, data: Value) {
+        self.set_response(endpoint, data.clone()).await;
+        self.publish(endpoint, "Update", data);
+    }
+
+    pub fn publish(&self, endpoint: &str, event_type: &str, data: Value) {
+        let subscription = subscription_name(endpoint);
+        let frame = json!([
+            8,
+            subscription,
+            { "data": data, "eventType": event_type, "uri": endpoint }
+        ]);
+
+        // No receivers just means nobody is subscribed yet.
+        let _ = self.state.events.send((subscription, frame.to_string()));
+    }
+
+    pub async fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.requests.lock().await.clone()
+    }
+
+    /// Waits until a websocket client has subscribed to `endpoint`, so published events
+    /// aren't lost.
+    pub async fn wait_for_subscription(&self, endpoint: &str, timeout: Duration) -> bool {
+        let subscription = subscription_name(endpoint);
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            if self
+                .state
+                .subscriptions
+                .lock()
+                .await
+                .contains(&subscription)
+            {
+                return true;
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// Polls the request log until a matching request shows up or `timeout` elapses.
+    pub async fn wait_for_request(
+        &self,
+        method: &str,
+        path: &str,
+        timeout: Duration,
+    ) -> Option<RecordedRequest> {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            let found = self
+                .requests()
+                .await
+                .into_iter()
+                .find(|r| r.method == method && r.path.starts_with(path));
+
+            if found.is_some() || tokio::time::Instant::now() >= deadline {
+                return found;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
+
+impl Drop for MockLcu {
+    fn drop(&mut self) {
+        self.handle.shutdown();
+    }
+}
+
+fn default_responses() -> HashMap<String, Value> {
+    HashMap::from([
+        ("/lol-gameflow/v1/gameflow-phase".to_string(), json!("None")),
+        (
+            "/riotclient/region-locale".to_string(),
+            json!({
+                "locale": "en_US",
+                "region": "NA",
+                "webLanguage": "en",
+                "webRegion": "NA"
+            }),
+        ),
+        (
+            "/chat/v5/participants".to_string(),
+            json!({ "participants": [] }),
+        ),
+        (
+            "/lol-champ-select/v1/session".to_string(),
+            champ_select_session(1, "PLANNING", 30000),
+        ),
+        (
+            "/lol-end-of-game/v1/eog-stats-block".to_string(),
+            json!({ "gameId": 1, "localPlayer": {}, "teams": [] }),
+        ),
+        ("/lol-chat/v1/friends".to_string(), json!([])),
+    ])
+}
+
+/// A minimal `/lol-champ-select/v1/session` body accepted by `ChampSelectSession`.
+pub fn champ_select_session(game_id: u64, phase: &str, time_left: u64) -> Value {
+    json!({
+        "allowBattleBoost": false,
+        "allowDuplicatePicks": false,
+        "allowLockedEvents": false,
+        "allowRerolling": false,
+        "allowSkinSelection": true,
+        "benchEnabled": false,
+        "boostableSkinCount": 0,
+        "counter": 1,
+        "gameId": game_id,
+        "hasSimultaneousBans": true,
+        "hasSimultaneousPicks": false,
+        "isCustomGame": false,
+        "isSpectating": false,
+        "localPlayerCellId": 0,
+        "lockedEventIndex": -1,
+        "recoveryCounter": 0,
+        "rerollsRemaining": 0,
+        "skipChampionSelect": false,
+        "timer": {
+            "adjustedTimeLeftInPhase": time_left,
+            "internalNowInEpochMs": 0,
+            "isInfinite": false,
+            "phase": phase,
+            "totalTimeInPhase": time_left
+        }
+    })
+}
+
+/// A chat participant as listed by `/chat/v5/participants` during champ select.
+pub fn participant(game_name: &str, game_tag: &str) -> Value {
+    json!({
+        "cid": "champ-select-1@champ-select.na1.pvp.net",
+        "game_name": game_name,
+        "game_tag": game_tag,
+        "muted": false,
+        "name": game_name,
+        "pid": format!("{}@na1.pvp.net", game_name),
+        "puuid": format!("{}-puuid", game_name),
+        "region": "na1"
+    })
+}
+
+fn authorized(state: &MockState, headers: &HeaderMap) -> bool {
+    let expected = format!("Basic {}", STANDARD.encode(format!("riot:{}", state.token)));
+    headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .map(|h| h == expected)
+        .unwrap_or(false)
+}
+
+async fn serve_rest(
+    State(state): State<Arc<MockState>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if !authorized(&state, &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    let path = uri
+        .path_and_query()
+        .map(|p| p.as_str())
+        .unwrap_or(uri.path())
+        .to_string();
+
+    state.requests.lock().await.push(RecordedRequest {
+        method: method.to_string(),
+        path: path.clone(),
+        body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+    });
+
+    if method != Method::GET {
+        return StatusCode::NO_CONTENT.into_response();
+    }
+
+    match state.responses.lock().await.get(uri.path()) {
+        Some(value) => Json(value.clone()).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "errorCode": "RPC_ERROR",
+                "httpStatus": 404,
+                "message": format!("Invalid URI format: {}", path)
+            })),
+        )
+            .into_response(),
+    }
+}
+
+async fn serve_websocket(
+    State(state): State<Arc<MockState>>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if !authorized(&state, &headers) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    ws.on_upgrade(move |socket| serve_socket(socket, state))
+}
+
+async fn serve_socket(mut socket: WebSocket, state: Arc<MockState>) {
+    let mut events = state.events.subscribe();
+    let mut subscriptions = HashSet::new();
+
+    loop {
+        tokio::select! {
+            msg = socket.recv() => {
+                let Some(Ok(Message::Text(text))) = msg else { return };
+                let Ok((opcode, name)) = serde_json::from_str::<(u8, String)>(&text) else {
+                    continue;
+                };
+
+                match opcode {
+                    5 => {
+                        state.subscriptions.lock().await.insert(name.clone());
+                        subscriptions.insert(name);
+                    }
+                    6 => {
+                        subscriptions.remove(&name);
+                    }
+                    _ => {}
+                }
+            }
+
+            event = events.recv() => {
+                let (subscription, frame) = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                if subscriptions.contains(&subscription)
+                    && socket.send(Message::Text(frame)).await.is_err()
+                {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        champ_select::{notify_pick_turn, ChampSelectSession},
+        config::ConfigFile,
+        dodge::{cancel_dodge, schedule_dodge},
+        end_game::{submit_report, ReportCategory, ReportRequest},
+        error::CommandError,
+        handle_ws_message,
+        lcu::{LcuConnection, LcuWebsocket},
+        manage_state,
+        match_db::{query_db, MatchDb},
+        notifications::NotificationConfig,
+        ready_check::ReadyCheckAction,
+        state::{get_gameflow_state, handle_client_state, GameflowPhase},
+        Config, Headless, Host, ManagedDodgeState, ManagedMatchDb, ManagedReportState,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn test_config() -> Config {
+        Config {
+            auto_open: false,
+            auto_accept: true,
+            accept_delay: 0,
+            report_review: true,
+            dodge_lead_time: 0,
+            ..Default::default()
+        }
+    }
+
+    fn in_progress(timer: f64) -> Value {
+        json!({ "state": "InProgress", "playerResponse": "None", "timer": timer })
+    }
+
+    /// Payloads of every `event` the host sends from now on.
+    fn events(host: &Headless, event: &'static str) -> tokio::sync::mpsc::UnboundedReceiver<Value> {
+        let mut events = host.subscribe();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok(sent) = events.recv().await {
+                if sent.event == event && tx.send(sent.payload).is_err() {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+
+    async fn setup(cfg: Config) -> (MockLcu, Headless, LcuConnection) {
+        let mock = MockLcu::start().await.unwrap();
+        let mut profiles = ConfigFile::default();
+        profiles.set_active_config(cfg);
+        let mut builder = Headless::builder();
+        manage_state(&mut builder, profiles, MatchDb::open_in_memory().unwrap());
+
+        let args = mock.connection_source().args().unwrap();
+        let connection = LcuConnection::from_args(args).unwrap();
+
+        (mock, builder.build(), connection)
+    }
+
+    #[tokio::test]
+    async fn websocket_receives_subscribed_events() {
+        let (mock, _host, connection) = setup(test_config()).await;
+        let endpoint = "/lol-gameflow/v1/gameflow-phase";
+
+        let mut ws = LcuWebsocket::connect(&connection.auth).await.unwrap();
+        ws.subscribe(endpoint).await.unwrap();
+        assert!(mock.wait_for_subscription(endpoint, TIMEOUT).await);
+
+        mock.update(endpoint, json!("ReadyCheck")).await;
+
+        let event = ws.next().await.unwrap();
+        assert_eq!(event.subscription, subscription_name(endpoint));
+        assert_eq!(event.uri, endpoint);
+        assert_eq!(event.data, json!("ReadyCheck"));
+    }
+
+    #[tokio::test]
+    async fn ready_check_is_accepted_once() {
+        let (mock, host, connection) = setup(test_config()).await;
+
+        mock.set_response("/lol-gameflow/v1/gameflow-phase", json!("ReadyCheck"))
+            .await;
+        mock.set_response("/lol-matchmaking/v1/search", json!({ "queueId": 420 }))
+            .await;
+        mock.set_response("/lol-matchmaking/v1/ready-check", in_progress(1.0))
+            .await;
+        let phase = get_gameflow_state(&connection.remoting_client)
+            .await
+            .unwrap();
+        assert_eq!(phase, GameflowPhase::ReadyCheck);
+
+        for _ in 0..2 {
+            handle_client_state(
+                phase.clone(),
+                &host,
+                &connection.remoting_client,
+                &connection.app_client,
+            )
+            .await;
+        }
+
+        let accept = "/lol-matchmaking/v1/ready-check/accept";
+        assert!(mock
+            .wait_for_request("POST", accept, TIMEOUT)
+            .await
+            .is_some());
+
+        let accepts = mock
+            .requests()
+            .await
+            .into_iter()
+            .filter(|r| r.path == accept)
+            .count();
+        assert_eq!(accepts, 1);
+    }
+
+    #[tokio::test]
+    async fn ready_check_follows_queue_rules() {
+        let mut cfg = test_config();
+        cfg.accept_rules.queues = HashMap::from([(450, ReadyCheckAction::Decline)]);
+        cfg.accept_rules.decline_when_away = true;
+        let (mock, host, connection) = setup(cfg).await;
+        let mut results = events(&host, "ready_check_result");
+
+        mock.set_response("/lol-matchmaking/v1/search", json!({ "queueId": 450 }))
+            .await;
+        mock.set_response("/lol-matchmaking/v1/ready-check", in_progress(0.0))
+            .await;
+        handle_client_state(
+            GameflowPhase::ReadyCheck,
+            &host,
+            &connection.remoting_client,
+            &connection.app_client,
+        )
+        .await;
+
+        let decline = "/lol-matchmaking/v1/ready-check/decline";
+        assert!(mock
+            .wait_for_request("POST", decline, TIMEOUT)
+            .await
+            .is_some());
+
+        // Ranked is accepted by default, unless the client says we're away.
+        mock.set_response("/lol-matchmaking/v1/search", json!({ "queueId": 420 }))
+            .await;
+        mock.set_response("/lol-chat/v1/me", json!({ "availability": "away" }))
+            .await;
+        for phase in [GameflowPhase::Matchmaking, GameflowPhase::ReadyCheck] {
+            handle_client_state(
+                phase,
+                &host,
+                &connection.remoting_client,
+                &connection.app_client,
+            )
+            .await;
+        }
+
+        for _ in 0..2 {
+            let result = tokio::time::timeout(TIMEOUT, results.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(result["action"], "decline");
+            assert_eq!(result["outcome"], "sent");
+        }
+
+        let requests = mock.requests().await;
+        assert_eq!(requests.iter().filter(|r| r.path == decline).count(), 2);
+        assert!(requests
+            .iter()
+            .all(|r| r.path != "/lol-matchmaking/v1/ready-check/accept"));
+    }
+
+    #[tokio::test]
+    async fn pending_accept_is_cancelled_when_the_ready_check_ends() {
+        let mut cfg = test_config();
+        cfg.accept_delay = 5000;
+        let (mock, host, connection) = setup(cfg).await;
+        let mut results = events(&host, "ready_check_result");
+
+        mock.set_response("/lol-matchmaking/v1/search", json!({ "queueId": 420 }))
+            .await;
+        // Popped 4.5s ago, so the accept is due in half a second.
+        mock.set_response("/lol-matchmaking/v1/ready-check", in_progress(4.5))
+            .await;
+
+        // Someone declined right away.
+        for phase in [GameflowPhase::ReadyCheck, GameflowPhase::Matchmaking] {
+            handle_client_state(
+                phase,
+                &host,
+                &connection.remoting_client,
+                &connection.app_client,
+            )
+            .await;
+        }
+
+        let result = tokio::time::timeout(TIMEOUT, results.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result["outcome"], "cancelled");
+        assert_eq!(result["reason"], "ready check ended");
+
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+        assert!(mock
+            .requests()
+            .await
+            .iter()
+            .all(|r| r.path != "/lol-matchmaking/v1/ready-check/accept"));
+    }
+
+    #[tokio::test]
+    async fn champ_select_emits_team_on_chat_events() {
+        let (mock, host, connection) = setup(test_config()).await;
+        let endpoint = "/chat/v5/participants";
+
+        let mut rx = events(&host, "champ_select_started");
+
+        // We won a game with "two" before.
+        {
+            let match_db = host.state::<ManagedMatchDb>();
+            let match_db = match_db.0.lock().unwrap();
+            let raw = json!({
+                "gameId": 1,
+                "teams": [{ "isPlayerTeam": true, "isWinningTeam": true }]
+            });
+            let stats = serde_json::from_value(raw.clone()).unwrap();
+            match_db.record_end_of_game(&stats, &raw, 1000).unwrap();
+            let two = serde_json::from_value(participant("two", "NA1")).unwrap();
+            match_db.record_teammates(1, &[two]).unwrap();
+        }
+
+        let mut ws = LcuWebsocket::connect(&connection.auth).await.unwrap();
+        ws.subscribe(endpoint).await.unwrap();
+        assert!(mock.wait_for_subscription(endpoint, TIMEOUT).await);
+
+        // Nobody joined yet, nothing to emit.
+        handle_client_state(
+            GameflowPhase::ChampSelect,
+            &host,
+            &connection.remoting_client,
+            &connection.app_client,
+        )
+        .await;
+        assert!(rx.try_recv().is_err());
+
+        for joined in [2, 5] {
+            let team = ["one", "two", "three", "four", "five"][..joined]
+                .iter()
+                .map(|name| participant(name, "NA1"))
+                .collect::<Vec<_>>();
+            mock.update(endpoint, json!({ "participants": team })).await;
+
+            let event = ws.next().await.unwrap();
+            handle_ws_message(
+                event,
+                &host,
+                &connection.remoting_client,
+                &connection.app_client,
+            )
+            .await;
+
+            let payload = tokio::time::timeout(TIMEOUT, rx.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(payload["participants"].as_array().unwrap().len(), joined);
+            assert_eq!(payload["participants"][0]["played_with"], Value::Null);
+            assert_eq!(payload["participants"][1]["game_name"], "two");
+            assert_eq!(payload["participants"][1]["played_with"]["games"], 1);
+            assert_eq!(payload["participants"][1]["played_with"]["winRate"], 1.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn finalization_dodge_is_sent() {
+        let (mock, host, connection) = setup(test_config()).await;
+        let endpoint = "/lol-champ-select/v1/session";
+
+        host.state::<ManagedDodgeState>().0.lock().await.enabled = Some(42);
+
+        let mut ws = LcuWebsocket::connect(&connection.auth).await.unwrap();
+        ws.subscribe(endpoint).await.unwrap();
+        assert!(mock.wait_for_subscription(endpoint, TIMEOUT).await);
+
+        mock.update(endpoint, champ_select_session(42, "FINALIZATION", 0))
+            .await;
+
+        let event = ws.next().await.unwrap();
+        handle_ws_message(
+            event,
+            &host,
+            &connection.remoting_client,
+            &connection.app_client,
+        )
+        .await;
+
+        let dodge = mock
+            .wait_for_request("POST", "/lol-login/v1/session/invoke", TIMEOUT)
+            .await
+            .unwrap();
+        assert!(dodge.path.contains("quitV2"));
+    }
+
+    /// Enables the dodge for game 42 and schedules it from a finalization session with
+    /// `time_left` ms to go.
+    async fn schedule_finalization_dodge(
+        mock: &MockLcu,
+        host: &Headless,
+        connection: &LcuConnection,
+        time_left: u64,
+    ) {
+        let session = champ_select_session(42, "FINALIZATION", time_left);
+        mock.set_response("/lol-champ-select/v1/session", session.clone())
+            .await;
+        host.state::<ManagedDodgeState>().0.lock().await.enabled = Some(42);
+
+        let session: ChampSelectSession = serde_json::from_value(session).unwrap();
+        schedule_dodge(&session, host, &connection.remoting_client).await;
+    }
+
+    fn quit_requests(requests: &[RecordedRequest]) -> usize {
+        requests
+            .iter()
+            .filter(|r| r.path.contains("quitV2"))
+            .count()
+    }
+
+    #[tokio::test]
+    async fn dodge_fires_lead_time_before_finalization_ends() {
+        let mut cfg = test_config();
+        cfg.dodge_lead_time = 1000;
+        let (mock, host, connection) = setup(cfg).await;
+        let mut scheduled = events(&host, "dodge_scheduled");
+        let mut sent = events(&host, "dodge_sent");
+
+        let start = tokio::time::Instant::now();
+        schedule_finalization_dodge(&mock, &host, &connection, 1500).await;
+
+        let payload = scheduled.recv().await.unwrap();
+        assert_eq!(payload["gameId"], 42);
+        assert_eq!(payload["firesInMs"], 500);
+
+        // Another session update doesn't schedule it twice.
+        schedule_finalization_dodge(&mock, &host, &connection, 1400).await;
+        assert!(scheduled.try_recv().is_err());
+
+        let payload = tokio::time::timeout(TIMEOUT, sent.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(payload["error"], Value::Null);
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        assert_eq!(quit_requests(&mock.requests().await), 1);
+    }
+
+    #[tokio::test]
+    async fn cancelled_dodge_is_never_sent() {
+        let mut cfg = test_config();
+        cfg.dodge_lead_time = 1000;
+        let (mock, host, connection) = setup(cfg).await;
+        let mut cancelled = events(&host, "dodge_cancelled");
+
+        schedule_finalization_dodge(&mock, &host, &connection, 1300).await;
+        cancel_dodge(&host, "dodge disabled").await;
+
+        let payload = cancelled.recv().await.unwrap();
+        assert_eq!(payload["gameId"], 42);
+        assert_eq!(payload["reason"], "dodge disabled");
+
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert_eq!(quit_requests(&mock.requests().await), 0);
+        assert!(host
+            .state::<ManagedDodgeState>()
+            .0
+            .lock()
+            .await
+            .scheduled
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn dodge_is_skipped_once_champ_select_moved_on() {
+        let cases = [
+            (
+                champ_select_session(42, "GAME_STARTING", 0),
+                "no longer in finalization",
+            ),
+            (
+                champ_select_session(43, "FINALIZATION", 5000),
+                "champ select changed",
+            ),
+        ];
+
+        for (session, reason) in cases {
+            let mut cfg = test_config();
+            cfg.dodge_lead_time = 1000;
+            let (mock, host, connection) = setup(cfg).await;
+            let mut cancelled = events(&host, "dodge_cancelled");
+
+            schedule_finalization_dodge(&mock, &host, &connection, 1200).await;
+            mock.set_response("/lol-champ-select/v1/session", session)
+                .await;
+
+            let payload = tokio::time::timeout(TIMEOUT, cancelled.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(payload["reason"], reason);
+
+            let requests = mock.requests().await;
+            assert!(requests
+                .iter()
+                .any(|r| r.method == "GET" && r.path == "/lol-champ-select/v1/session"));
+            assert_eq!(quit_requests(&requests), 0);
+            assert_eq!(
+                host.state::<ManagedDodgeState>().0.lock().await.last_dodge,
+                None
+            );
+        }
+    }
+
+    /// Titles of the notifications shown while a ready check, champ select, pick turn and
+    /// dodge go by with only `notifications` enabled.
+    async fn notifications_shown(notifications: NotificationConfig) -> Vec<String> {
+        let cfg = Config {
+            auto_accept: false,
+            notifications,
+            ..test_config()
+        };
+        let (mock, host, connection) = setup(cfg).await;
+        let mut shown = events(&host, "notification");
+        let mut sent = events(&host, "dodge_sent");
+
+        for phase in [GameflowPhase::ReadyCheck, GameflowPhase::ChampSelect] {
+            handle_client_state(
+                phase,
+                &host,
+                &connection.remoting_client,
+                &connection.app_client,
+            )
+            .await;
+        }
+
+        let mut session = champ_select_session(42, "BAN_PICK", 30000);
+        session["actions"] = json!([[
+            { "id": 1, "actorCellId": 0, "championId": 0, "completed": false,
+              "isAllyAction": true, "isInProgress": true, "type": "pick" }
+        ]]);
+        let session: ChampSelectSession = serde_json::from_value(session).unwrap();
+        notify_pick_turn(&session, &host).await;
+
+        schedule_finalization_dodge(&mock, &host, &connection, 0).await;
+        tokio::time::timeout(TIMEOUT, sent.recv())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let mut titles = Vec::new();
+        while let Ok(notification) = shown.try_recv() {
+            titles.push(notification["title"].as_str().unwrap().to_string());
+        }
+        titles
+    }
+
+    #[tokio::test]
+    async fn notifications_follow_their_toggles() {
+        assert!(notifications_shown(NotificationConfig::default())
+            .await
+            .is_empty());
+
+        let cases = [
+            (
+                NotificationConfig {
+                    ready_check: true,
+                    ..Default::default()
+                },
+                "Match found",
+            ),
+            (
+                NotificationConfig {
+                    champ_select: true,
+                    ..Default::default()
+                },
+                "Champ select started",
+            ),
+            (
+                NotificationConfig {
+                    pick_turn: true,
+                    ..Default::default()
+                },
+                "Your turn to pick",
+            ),
+            (
+                NotificationConfig {
+                    dodge: true,
+                    ..Default::default()
+                },
+                "Last second dodge",
+            ),
+        ];
+        for (notifications, title) in cases {
+            assert_eq!(notifications_shown(notifications).await, vec![title]);
+        }
+    }
+
+    #[tokio::test]
+    async fn end_of_game_queues_players_for_review() {
+        let (mock, host, connection) = setup(test_config()).await;
+        let path = "/lol-player-report-sender/v1/end-of-game-reports";
+
+        mock.set_response(
+            "/lol-end-of-game/v1/eog-stats-block",
+            json!({
+                "gameId": 7,
+                "localPlayer": { "summonerId": 1, "puuid": "me" },
+                "teams": [{
+                    "isPlayerTeam": true,
+                    "players": [
+                        { "summonerId": 1, "puuid": "me" },
+                        { "summonerId": 2, "puuid": "them" }
+                    ]
+                }]
+            }),
+        )
+        .await;
+
+        handle_client_state(
+            GameflowPhase::EndOfGame,
+            &host,
+            &connection.remoting_client,
+            &connection.app_client,
+        )
+        .await;
+
+        let queue = tokio::time::timeout(TIMEOUT, async {
+            loop {
+                if let Some(queue) = host
+                    .state::<ManagedReportState>()
+                    .0
+                    .lock()
+                    .await
+                    .queue
+                    .clone()
+                {
+                    return queue;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(queue.players.len(), 1);
+        assert_eq!(queue.players[0].puuid, "them");
+
+        let report_state = host.state::<ManagedReportState>();
+        let last_game = report_state.0.lock().await.last_game.clone().unwrap();
+        assert_eq!(last_game.game_id, 7);
+        // Not a ranked game, the client has no LP change for it.
+        assert!(last_game.lp_change.is_none());
+
+        // Nothing is sent until the user picks a player and categories.
+        assert!(mock.requests().await.iter().all(|r| r.path != path));
+
+        let request = |categories| ReportRequest {
+            game_id: 7,
+            puuid: "them".to_string(),
+            categories,
+            comment: String::new(),
+        };
+        assert!(matches!(
+            submit_report(&host, &connection.remoting_client, request(vec![])).await,
+            Err(CommandError::InvalidReport(_))
+        ));
+
+        let queue = submit_report(
+            &host,
+            &connection.remoting_client,
+            request(vec![ReportCategory::VerbalAbuse]),
+        )
+        .await
+        .unwrap();
+        assert!(queue.players[0].reported);
+
+        let report = mock.wait_for_request("POST", path, TIMEOUT).await.unwrap();
+        assert_eq!(report.body["offenderPuuid"], "them");
+        assert_eq!(report.body["gameId"], 7);
+        assert_eq!(report.body["categories"], json!(["VERBAL_ABUSE"]));
+
+        let games = query_db(&host, |db| db.recent_games(10)).await.unwrap();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].game_id, 7);
+    }
+}
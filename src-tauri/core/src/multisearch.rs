@@ -0,0 +1,253 @@
+use crate::{error::CommandError, lobby::Participant, region::Region, Config};
+use serde::{Deserialize, Serialize};
+use urlencoding::encode;
+
+/// A site that can look up a whole team at once.
+pub trait MultiSearchProvider: Send + Sync {
+    /// Stored as `multiProvider` in the config.
+    fn id(&self) -> &str;
+    fn name(&self) -> &str;
+    fn link(&self, summoners: &[Participant], region: &Region) -> String;
+}
+
+/// A provider described by a URL template, used for the built-ins and user-defined providers.
+///
+/// Placeholders:
+/// - `{region}`, `{regionLower}`: the site's own slug for built-ins, otherwise the shard name
+///   (`EUNE`)
+/// - `{platform}`, `{platformLower}`: the platform id (`EUN1`)
+/// - `{cluster}`: the routing cluster (`europe`)
+///
+/// Regions we don't know fill all of them but `{cluster}` with the region the client reports.
+/// - `{names}`: the encoded Riot IDs joined by `separator`, each as `name{tagSeparator}tag`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateProvider {
+    pub id: String,
+    pub name: String,
+    pub url_template: String,
+    #[serde(default = "default_separator")]
+    pub separator: String,
+    #[serde(default = "default_tag_separator")]
+    pub tag_separator: String,
+}
+
+fn default_separator() -> String {
+    ",".to_string()
+}
+
+fn default_tag_separator() -> String {
+    "#".to_string()
+}
+
+impl TemplateProvider {
+    fn builtin(id: &str, name: &str, url_template: &str, tag_separator: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            url_template: url_template.to_string(),
+            separator: default_separator(),
+            tag_separator: tag_separator.to_string(),
+        }
+    }
+}
+
+impl MultiSearchProvider for TemplateProvider {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn link(&self, summoners: &[Participant], region: &Region) -> String {
+        let slug = region.slug(&self.id);
+        let platform_id = region.platform_id();
+        let cluster = region.cluster().map(|c| c.as_str()).unwrap_or_default();
+        let names = summoners
+            .iter()
+            .map(|s| format!("{}{}{}", s.game_name, self.tag_separator, s.game_tag))
+            .collect::<Vec<_>>()
+            .join(&self.separator);
+
+        self.url_template
+            .replace("{regionLower}", &slug.to_lowercase())
+            .replace("{region}", slug)
+            .replace("{platformLower}", &platform_id.to_lowercase())
+            .replace("{platform}", platform_id)
+            .replace("{cluster}", cluster)
+            .replace("{names}", &encode(&names))
+    }
+}
+
+fn builtins() -> Vec<TemplateProvider> {
+    vec![
+        TemplateProvider::builtin(
+            "opgg",
+            "OP.GG",
+            "https://www.op.gg/multisearch/{region}?summoners={names}",
+            "#",
+        ),
+        TemplateProvider::builtin(
+            "deeplol",
+            "DeepLoL",
+            "https://deeplol.gg/multi/{region}/{names}",
+            "#",
+        ),
+        TemplateProvider::builtin(
+            "ugg",
+            "U.GG",
+            "https://u.gg/multisearch?region={region}&summoners={names}",
+            "-",
+        ),
+        TemplateProvider::builtin(
+            "tracker",
+            "Tracker.gg",
+            "https://tracker.gg/lol/multisearch/{region}/{names}",
+            "#",
+        ),
+    ]
+}
+
+/// Listed by `list_providers`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderInfo {
+    pub id: String,
+    pub name: String,
+}
+
+/// The built-in providers followed by the ones from the config. A custom provider with a
+/// built-in id replaces it.
+pub struct ProviderRegistry {
+    providers: Vec<Box<dyn MultiSearchProvider>>,
+}
+
+impl ProviderRegistry {
+    pub fn from_config(config: &Config) -> Self {
+        let mut registry = Self {
+            providers: Vec::new(),
+        };
+
+        for provider in builtins()
+            .into_iter()
+            .chain(config.custom_providers.clone())
+        {
+            registry.register(Box::new(provider));
+        }
+
+        registry
+    }
+
+    pub fn register(&mut self, provider: Box<dyn MultiSearchProvider>) {
+        self.providers.retain(|p| p.id() != provider.id());
+        self.providers.push(provider);
+    }
+
+    pub fn get(&self, id: &str) -> Result<&dyn MultiSearchProvider, CommandError> {
+        self.providers
+            .iter()
+            .find(|p| p.id() == id)
+            .map(|p| p.as_ref())
+            .ok_or_else(|| CommandError::UnknownProvider(id.to_string()))
+    }
+
+    pub fn list(&self) -> Vec<ProviderInfo> {
+        self.providers
+            .iter()
+            .map(|p| ProviderInfo {
+                id: p.id().to_string(),
+                name: p.name().to_string(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region::Shard;
+
+    fn summoner(game_name: &str, game_tag: &str) -> Participant {
+        Participant {
+            cid: String::new(),
+            game_name: game_name.to_string(),
+            game_tag: game_tag.to_string(),
+            muted: false,
+            name: game_name.to_string(),
+            pid: String::new(),
+            puuid: String::new(),
+            region: String::new(),
+        }
+    }
+
+    fn link(config: &Config, id: &str, region: &str) -> String {
+        let region = match Shard::find(region) {
+            Some(shard) => Region::Shard(shard),
+            None => Region::Unlisted(region.to_string()),
+        };
+
+        let team = [summoner("Faker", "KR1"), summoner("Hide on bush", "T1")];
+        ProviderRegistry::from_config(config)
+            .get(id)
+            .unwrap()
+            .link(&team, &region)
+    }
+
+    #[test]
+    fn builtins_use_their_region_slugs() {
+        let config = Config::default();
+
+        assert_eq!(
+            link(&config, "opgg", "KR"),
+            "https://www.op.gg/multisearch/kr?summoners=Faker%23KR1%2CHide%20on%20bush%23T1"
+        );
+        assert_eq!(
+            link(&config, "ugg", "KR"),
+            "https://u.gg/multisearch?region=kr&summoners=Faker-KR1%2CHide%20on%20bush-T1"
+        );
+        assert_eq!(
+            link(&config, "ugg", "EUNE"),
+            "https://u.gg/multisearch?region=eun1&summoners=Faker-KR1%2CHide%20on%20bush-T1"
+        );
+        assert_eq!(
+            link(&config, "tracker", "OCE"),
+            "https://tracker.gg/lol/multisearch/OCE/Faker%23KR1%2CHide%20on%20bush%23T1"
+        );
+        assert_eq!(
+            link(&config, "opgg", "PBE"),
+            "https://www.op.gg/multisearch/PBE?summoners=Faker%23KR1%2CHide%20on%20bush%23T1"
+        );
+    }
+
+    #[test]
+    fn custom_providers_use_their_template() {
+        let config = Config {
+            custom_providers: vec![TemplateProvider {
+                id: "example".to_string(),
+                name: "Example".to_string(),
+                url_template: "https://example.gg/{cluster}/{platformLower}/{regionLower}/{names}"
+                    .to_string(),
+                separator: ",".to_string(),
+                tag_separator: "-".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            link(&config, "example", "EUNE"),
+            "https://example.gg/europe/eun1/eune/Faker-KR1%2CHide%20on%20bush-T1"
+        );
+        assert_eq!(ProviderRegistry::from_config(&config).list().len(), 5);
+    }
+
+    #[test]
+    fn unknown_providers_are_an_error() {
+        let registry = ProviderRegistry::from_config(&Config::default());
+
+        assert!(matches!(
+            registry.get("nope"),
+            Err(CommandError::UnknownProvider(id)) if id == "nope"
+        ));
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{AppConfig, Host};
+use serde::{Deserialize, Serialize};
+
+/// Which events raise a desktop notification, everything is off until the user opts in.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationConfig {
+    pub ready_check: bool,
+    pub champ_select: bool,
+    pub pick_turn: bool,
+    pub dodge: bool,
+    pub sound: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyEvent {
+    ReadyCheck,
+    ChampSelect,
+    PickTurn,
+    Dodge,
+}
+
+impl NotificationConfig {
+    pub fn is_enabled(&self, event: NotifyEvent) -> bool {
+        match event {
+            NotifyEvent::ReadyCheck => self.ready_check,
+            NotifyEvent::ChampSelect => self.champ_select,
+            NotifyEvent::PickTurn => self.pick_turn,
+            NotifyEvent::Dodge => self.dodge,
+        }
+    }
+}
+
+/// Shows a native notification if the user enabled `event`.
+pub async fn notify<H: Host>(host: &H, event: NotifyEvent, title: &str, body: &str) {
+    let cfg = {
+        let cfg = host.state::<AppConfig>();
+        let cfg = cfg.0.lock().await;
+        cfg.notifications.clone()
+    };
+
+    if cfg.is_enabled(event) {
+        host.notify(title, body, cfg.sound);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notifications_are_opt_in() {
+        let cfg = NotificationConfig::default();
+        assert!(!cfg.is_enabled(NotifyEvent::ReadyCheck));
+        assert!(!cfg.is_enabled(NotifyEvent::ChampSelect));
+        assert!(!cfg.is_enabled(NotifyEvent::PickTurn));
+        assert!(!cfg.is_enabled(NotifyEvent::Dodge));
+    }
+
+    #[test]
+    fn each_event_has_its_own_toggle() {
+        let cfg: NotificationConfig =
+            serde_json::from_str(r#"{"pickTurn": true, "sound": true}"#).unwrap();
+        assert!(cfg.is_enabled(NotifyEvent::PickTurn));
+        assert!(!cfg.is_enabled(NotifyEvent::ReadyCheck));
+        assert!(!cfg.is_enabled(NotifyEvent::Dodge));
+        assert!(cfg.sound);
+    }
+}
@@ -0,0 +1,530 @@
+use crate::{
+    error::{lcu_response, parse_response, CommandError},
+    lcu::LcuClient,
+    utils::lenient,
+    AppConfig, Host, ManagedReadyCheckState,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How long the client waits for everyone to answer.
+const READY_CHECK_MS: u64 = 12_000;
+
+/// Responses go out at least this long before the ready check expires.
+const EXPIRY_MARGIN_MS: u64 = 1_000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReadyCheckAction {
+    #[default]
+    Accept,
+    Decline,
+    /// Leave the ready check to the user, only emit `ready_check`.
+    Notify,
+}
+
+/// Decides what auto accept does with each ready check.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AcceptRules {
+    /// By queue id: 420 ranked solo, 440 flex, 400 normal draft, 450 ARAM, custom lobbies
+    /// report 0.
+    pub queues: HashMap<i64, ReadyCheckAction>,
+    /// For queues without a rule.
+    pub default_action: ReadyCheckAction,
+    /// Decline while the client shows us as away, it sets that on its own after a few idle
+    /// minutes.
+    pub decline_when_away: bool,
+    /// Hand every ready check after this many auto accepts in a row to the user, 0 for no limit.
+    pub max_consecutive_accepts: u32,
+}
+
+impl Default for AcceptRules {
+    fn default() -> Self {
+        Self {
+            queues: HashMap::new(),
+            default_action: ReadyCheckAction::Accept,
+            decline_when_away: false,
+            max_consecutive_accepts: 0,
+        }
+    }
+}
+
+/// The parts of `/lol-matchmaking/v1/search` the rules look at.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MatchmakingSearch {
+    #[serde(deserialize_with = "lenient")]
+    pub queue_id: i64,
+}
+
+/// `/lol-matchmaking/v1/ready-check`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReadyCheck {
+    /// `InProgress` while waiting for answers.
+    pub state: String,
+    /// `None` until we accept or decline.
+    pub player_response: String,
+    /// Seconds since the ready check popped.
+    #[serde(deserialize_with = "lenient")]
+    pub timer: f64,
+}
+
+impl ReadyCheck {
+    fn is_waiting_for_us(&self) -> bool {
+        self.state == "InProgress" && matches!(self.player_response.as_str(), "" | "None")
+    }
+}
+
+/// An accept or decline waiting for `accept_delay`, aborted through `handle`.
+pub struct ScheduledResponse {
+    pub queue_id: i64,
+    pub action: ReadyCheckAction,
+    pub handle: JoinHandle<()>,
+}
+
+#[derive(Default)]
+pub struct ReadyCheckState {
+    /// Auto accepts the client took since the user last accepted one by hand.
+    consecutive_accepts: u32,
+    scheduled: Option<ScheduledResponse>,
+    /// Set while our own accept for the current ready check is out, so it isn't mistaken for
+    /// a manual one.
+    auto_accepted: bool,
+}
+
+impl ReadyCheckState {
+    /// A new ready check popped, nobody answered it yet.
+    fn start(&mut self) {
+        self.auto_accepted = false;
+    }
+
+    fn accept_sent(&mut self) {
+        self.consecutive_accepts += 1;
+    }
+
+    /// An accept we neither scheduled nor sent came from the user.
+    fn is_manual_accept(&self, ready_check: &ReadyCheck) -> bool {
+        ready_check.player_response == "Accepted" && self.scheduled.is_none() && !self.auto_accepted
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReadyCheckOutcome {
+    Sent,
+    Failed,
+    Cancelled,
+}
+
+/// How a scheduled response ended, emitted as `ready_check_result`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadyCheckResult {
+    pub queue_id: i64,
+    pub action: ReadyCheckAction,
+    pub outcome: ReadyCheckOutcome,
+    /// Why it was cancelled.
+    pub reason: Option<String>,
+    pub error: Option<CommandError>,
+}
+
+/// What was done with a ready check and why, emitted as `ready_check`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadyCheckDecision {
+    pub queue_id: i64,
+    pub action: ReadyCheckAction,
+    pub reason: String,
+}
+
+impl AcceptRules {
+    pub fn decide(
+        &self,
+        queue_id: i64,
+        away: bool,
+        consecutive_accepts: u32,
+    ) -> ReadyCheckDecision {
+        let decision = |action, reason: &str| ReadyCheckDecision {
+            queue_id,
+            action,
+            reason: reason.to_string(),
+        };
+
+        if self.decline_when_away && away {
+            return decision(ReadyCheckAction::Decline, "away from the client");
+        }
+
+        let action = self
+            .queues
+            .get(&queue_id)
+            .copied()
+            .unwrap_or(self.default_action);
+
+        if action == ReadyCheckAction::Accept
+            && self.max_consecutive_accepts > 0
+            && consecutive_accepts >= self.max_consecutive_accepts
+        {
+            return decision(
+                ReadyCheckAction::Notify,
+                "too many auto accepts in a row, accept this one yourself",
+            );
+        }
+
+        match self.queues.get(&queue_id) {
+            Some(_) => decision(action, "queue rule"),
+            None => decision(action, "default rule"),
+        }
+    }
+}
+
+/// How long to wait before answering a ready check that popped `elapsed_ms` ago.
+pub fn response_delay(accept_delay_ms: u64, elapsed_ms: u64) -> u64 {
+    accept_delay_ms
+        .min(READY_CHECK_MS - EXPIRY_MARGIN_MS)
+        .saturating_sub(elapsed_ms)
+}
+
+async fn fetch_ready_check(remoting_client: &LcuClient) -> Result<ReadyCheck, CommandError> {
+    parse_response(lcu_response(
+        remoting_client
+            .get("/lol-matchmaking/v1/ready-check".to_string())
+            .await,
+    )?)
+}
+
+async fn fetch_search(remoting_client: &LcuClient) -> Result<MatchmakingSearch, CommandError> {
+    parse_response(lcu_response(
+        remoting_client
+            .get("/lol-matchmaking/v1/search".to_string())
+            .await,
+    )?)
+}
+
+/// The client marks us `away` once we've been idle for a while.
+async fn is_away(remoting_client: &LcuClient) -> bool {
+    remoting_client
+        .get("/lol-chat/v1/me".to_string())
+        .await
+        .ok()
+        .and_then(|me| me.get("availability")?.as_str().map(|a| a == "away"))
+        .unwrap_or_default()
+}
+
+/// Applies the accept rules to the ready check that just popped and schedules the answer
+/// `accept_delay` ms after it started.
+pub async fn handle_ready_check<H: Host>(host: &H, remoting_client: &LcuClient) {
+    let (auto_accept, accept_delay, rules) = {
+        let cfg_state = host.state::<AppConfig>();
+        let cfg = cfg_state.0.lock().await;
+        (cfg.auto_accept, cfg.accept_delay, cfg.accept_rules.clone())
+    };
+
+    {
+        let state = host.state::<ManagedReadyCheckState>();
+        state.0.lock().await.start();
+    }
+
+    if !auto_accept {
+        return;
+    }
+
+    let ready_check = match fetch_ready_check(remoting_client).await {
+        Ok(ready_check) => ready_check,
+        Err(err) => {
+            println!("Failed to read ready check: {}", err);
+            return;
+        }
+    };
+
+    if !ready_check.is_waiting_for_us() {
+        return;
+    }
+
+    let search = match fetch_search(remoting_client).await {
+        Ok(search) => search,
+        Err(err) => {
+            println!("Failed to read matchmaking search: {}", err);
+            return;
+        }
+    };
+
+    let away = rules.decline_when_away && is_away(remoting_client).await;
+
+    let state = host.state::<ManagedReadyCheckState>();
+    let mut state = state.0.lock().await;
+    let decision = rules.decide(search.queue_id, away, state.consecutive_accepts);
+
+    println!(
+        "Ready check in queue {}: {:?} ({})",
+        decision.queue_id, decision.action, decision.reason
+    );
+    host.emit("ready_check", &decision);
+
+    if decision.action == ReadyCheckAction::Notify {
+        return;
+    }
+
+    let delay = response_delay(accept_delay as u64, (ready_check.timer * 1000.0) as u64);
+    let queue_id = decision.queue_id;
+    let action = decision.action;
+
+    let cloned_host = host.clone();
+    let cloned_remoting = remoting_client.clone();
+    let handle = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        fire_response(queue_id, action, &cloned_host, &cloned_remoting).await;
+    });
+
+    if let Some(previous) = state.scheduled.replace(ScheduledResponse {
+        queue_id,
+        action,
+        handle,
+    }) {
+        previous.handle.abort();
+    }
+}
+
+/// Resets the auto accept streak once the user accepts a ready check by hand.
+pub async fn handle_ready_check_update<H: Host>(data: Value, host: &H) {
+    let Ok(ready_check) = serde_json::from_value::<ReadyCheck>(data) else {
+        return;
+    };
+
+    let state = host.state::<ManagedReadyCheckState>();
+    let mut state = state.0.lock().await;
+    if state.consecutive_accepts > 0 && state.is_manual_accept(&ready_check) {
+        println!("Ready check accepted by hand, auto accept streak reset");
+        state.consecutive_accepts = 0;
+    }
+}
+
+/// Aborts a pending accept or decline, if there is one.
+pub async fn cancel_ready_check<H: Host>(host: &H, reason: &str) {
+    let scheduled = {
+        let state = host.state::<ManagedReadyCheckState>();
+        let mut state = state.0.lock().await;
+        state.scheduled.take()
+    };
+
+    if let Some(scheduled) = scheduled {
+        scheduled.handle.abort();
+        emit_result(
+            host,
+            ReadyCheckResult {
+                queue_id: scheduled.queue_id,
+                action: scheduled.action,
+                outcome: ReadyCheckOutcome::Cancelled,
+                reason: Some(reason.to_string()),
+                error: None,
+            },
+        );
+    }
+}
+
+fn emit_result<H: Host>(host: &H, result: ReadyCheckResult) {
+    println!(
+        "Ready check {:?}: {:?} {}",
+        result.action,
+        result.outcome,
+        result.reason.as_deref().unwrap_or_default()
+    );
+    host.emit("ready_check_result", result);
+}
+
+/// Re-checks that the ready check is still waiting on us, then answers it.
+async fn fire_response<H: Host>(
+    queue_id: i64,
+    action: ReadyCheckAction,
+    host: &H,
+    remoting_client: &LcuClient,
+) {
+    {
+        let state = host.state::<ManagedReadyCheckState>();
+        state.0.lock().await.scheduled = None;
+    }
+
+    let mut result = ReadyCheckResult {
+        queue_id,
+        action,
+        outcome: ReadyCheckOutcome::Cancelled,
+        reason: None,
+        error: None,
+    };
+
+    let reason = match fetch_ready_check(remoting_client).await {
+        Err(_) => Some("ready check ended"),
+        Ok(ready_check) if ready_check.state != "InProgress" => Some("ready check ended"),
+        Ok(ready_check) if !ready_check.is_waiting_for_us() => Some("already answered"),
+        Ok(_) => None,
+    };
+
+    if let Some(reason) = reason {
+        result.reason = Some(reason.to_string());
+        emit_result(host, result);
+        return;
+    }
+
+    let accepting = action != ReadyCheckAction::Decline;
+    if accepting {
+        let state = host.state::<ManagedReadyCheckState>();
+        state.0.lock().await.auto_accepted = true;
+    }
+
+    let endpoint = if accepting { "accept" } else { "decline" };
+
+    result.error = lcu_response(
+        remoting_client
+            .post(
+                format!("/lol-matchmaking/v1/ready-check/{}", endpoint),
+                serde_json::json!({}),
+            )
+            .await,
+    )
+    .err();
+    result.outcome = match result.error {
+        Some(_) => ReadyCheckOutcome::Failed,
+        None => ReadyCheckOutcome::Sent,
+    };
+
+    if accepting {
+        let state = host.state::<ManagedReadyCheckState>();
+        let mut state = state.0.lock().await;
+        match result.outcome {
+            ReadyCheckOutcome::Sent => state.accept_sent(),
+            _ => state.auto_accepted = false,
+        }
+    }
+
+    emit_result(host, result);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const QUEUE_RANKED_SOLO: i64 = 420;
+    const QUEUE_RANKED_FLEX: i64 = 440;
+    const QUEUE_NORMAL_DRAFT: i64 = 400;
+    const QUEUE_ARAM: i64 = 450;
+    const QUEUE_CUSTOM: i64 = 0;
+
+    fn rules() -> AcceptRules {
+        AcceptRules {
+            queues: HashMap::from([
+                (QUEUE_RANKED_SOLO, ReadyCheckAction::Accept),
+                (QUEUE_ARAM, ReadyCheckAction::Decline),
+                (QUEUE_CUSTOM, ReadyCheckAction::Notify),
+            ]),
+            default_action: ReadyCheckAction::Notify,
+            decline_when_away: true,
+            max_consecutive_accepts: 3,
+        }
+    }
+
+    #[test]
+    fn queues_use_their_rule_or_the_default() {
+        let rules = rules();
+
+        assert_eq!(
+            rules.decide(QUEUE_RANKED_SOLO, false, 0).action,
+            ReadyCheckAction::Accept
+        );
+        assert_eq!(
+            rules.decide(QUEUE_ARAM, false, 0).action,
+            ReadyCheckAction::Decline
+        );
+        assert_eq!(
+            rules.decide(QUEUE_NORMAL_DRAFT, false, 0).action,
+            ReadyCheckAction::Notify
+        );
+        assert_eq!(
+            AcceptRules::default()
+                .decide(QUEUE_RANKED_FLEX, false, 99)
+                .action,
+            ReadyCheckAction::Accept
+        );
+    }
+
+    #[test]
+    fn away_and_streaks_override_accepts() {
+        let rules = rules();
+
+        assert_eq!(
+            rules.decide(QUEUE_RANKED_SOLO, true, 0).action,
+            ReadyCheckAction::Decline
+        );
+        assert_eq!(
+            rules.decide(QUEUE_RANKED_SOLO, false, 2).action,
+            ReadyCheckAction::Accept
+        );
+        assert_eq!(
+            rules.decide(QUEUE_RANKED_SOLO, false, 3).action,
+            ReadyCheckAction::Notify
+        );
+    }
+
+    fn ready_check(player_response: &str) -> ReadyCheck {
+        serde_json::from_value(json!({
+            "state": "InProgress",
+            "playerResponse": player_response
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn only_accepts_we_did_not_send_are_manual() {
+        let mut state = ReadyCheckState::default();
+        state.start();
+        assert!(state.is_manual_accept(&ready_check("Accepted")));
+        assert!(!state.is_manual_accept(&ready_check("None")));
+        assert!(!state.is_manual_accept(&ready_check("Declined")));
+
+        state.scheduled = Some(ScheduledResponse {
+            queue_id: QUEUE_RANKED_SOLO,
+            action: ReadyCheckAction::Accept,
+            handle: tokio::spawn(async {}),
+        });
+        assert!(!state.is_manual_accept(&ready_check("Accepted")));
+
+        state.scheduled = None;
+        state.auto_accepted = true;
+        state.accept_sent();
+        assert!(!state.is_manual_accept(&ready_check("Accepted")));
+        assert_eq!(state.consecutive_accepts, 1);
+
+        // The next ready check is the user's again until we answer it.
+        state.start();
+        assert!(state.is_manual_accept(&ready_check("Accepted")));
+    }
+
+    #[test]
+    fn delay_counts_from_the_ready_check_start() {
+        assert_eq!(response_delay(2000, 0), 2000);
+        assert_eq!(response_delay(2000, 1500), 500);
+        assert_eq!(response_delay(2000, 3000), 0);
+        // Never so late that the check expires first.
+        assert_eq!(response_delay(30_000, 1000), 10_000);
+    }
+
+    #[test]
+    fn rules_round_trip_with_string_queue_ids() {
+        let parsed: AcceptRules = serde_json::from_value(json!({
+            "queues": { "420": "accept", "450": "decline" },
+            "maxConsecutiveAccepts": 5
+        }))
+        .unwrap();
+
+        assert_eq!(parsed.queues[&QUEUE_ARAM], ReadyCheckAction::Decline);
+        assert_eq!(parsed.default_action, ReadyCheckAction::Accept);
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap()["queues"]["420"],
+            "accept"
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use crate::lcu::{LcuEvent, LcuWebsocket};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+
+/// One line of a session recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Milliseconds since the unix epoch when the event was received.
+    pub timestamp: u64,
+    pub event: LcuEvent,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Drives the handlers from either the live websockets or a recording.
+pub enum EventSource {
+    /// The League client websocket, plus the Riot client one for chat when it's reachable.
+    Live(LcuWebsocket, Option<Box<LcuWebsocket>>),
+    Replay(EventReplay),
+}
+
+impl EventSource {
+    /// Ends with the League client stream, losing only the Riot client stream keeps going.
+    pub async fn next(&mut self) -> Option<LcuEvent> {
+        match self {
+            EventSource::Live(lcu, riot) => loop {
+                let Some(riot_ws) = riot.as_mut() else {
+                    return lcu.next().await;
+                };
+
+                let riot_event = tokio::select! {
+                    event = lcu.next() => return event,
+                    event = riot_ws.next() => event,
+                };
+
+                match riot_event {
+                    Some(event) => return Some(event),
+                    None => {
+                        println!("Riot Client event stream closed");
+                        *riot = None;
+                    }
+                }
+            },
+            EventSource::Replay(replay) => replay.next().await,
+        }
+    }
+}
+
+/// Appends every received event to a timestamped JSONL file.
+pub struct EventRecorder {
+    file: File,
+    path: PathBuf,
+}
+
+impl EventRecorder {
+    pub async fn create(dir: &Path) -> anyhow::Result<Self> {
+        tokio::fs::create_dir_all(dir).await?;
+
+        let path = dir.join(format!("session-{}.jsonl", now_ms()));
+        let file = File::create(&path).await?;
+
+        Ok(Self { file, path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub async fn record(&mut self, event: &LcuEvent) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(&RecordedEvent {
+            timestamp: now_ms(),
+            event: event.clone(),
+        })?;
+        line.push('\n');
+
+        // Flush every line so a crash still leaves a usable recording.
+        self.file.write_all(line.as_bytes()).await?;
+        self.file.flush().await?;
+        Ok(())
+    }
+}
+
+/// Feeds a recording back with the original spacing between events, divided by `speed`.
+/// A speed of zero replays everything as fast as possible.
+pub struct EventReplay {
+    lines: Lines<BufReader<File>>,
+    speed: f64,
+    last_timestamp: Option<u64>,
+}
+
+impl EventReplay {
+    pub async fn open(path: &Path, speed: f64) -> anyhow::Result<Self> {
+        let file = File::open(path).await?;
+
+        Ok(Self {
+            lines: BufReader::new(file).lines(),
+            speed,
+            last_timestamp: None,
+        })
+    }
+
+    /// `REVEAL_REPLAY` names the recording, `REVEAL_REPLAY_SPEED` defaults to real time.
+    pub fn from_env() -> Option<(PathBuf, f64)> {
+        let path = std::env::var("REVEAL_REPLAY").ok()?;
+        let speed = std::env::var("REVEAL_REPLAY_SPEED")
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .unwrap_or(1.0);
+
+        Some((PathBuf::from(path), speed))
+    }
+
+    pub async fn next(&mut self) -> Option<LcuEvent> {
+        loop {
+            let line = self.lines.next_line().await.ok()??;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let recorded = match serde_json::from_str::<RecordedEvent>(&line) {
+                Ok(recorded) => recorded,
+                Err(err) => {
+                    println!("Skipping malformed recording line: {}", err);
+                    continue;
+                }
+            };
+
+            if let Some(last) = self.last_timestamp {
+                let gap = recorded.timestamp.saturating_sub(last);
+                if self.speed > 0.0 && gap > 0 {
+                    let wait = Duration::from_secs_f64(gap as f64 / 1000.0 / self.speed);
+                    tokio::time::sleep(wait).await;
+                }
+            }
+
+            self.last_timestamp = Some(recorded.timestamp);
+            return Some(recorded.event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(data: serde_json::Value) -> LcuEvent {
+        LcuEvent {
+            subscription: "OnJsonApiEvent_lol-gameflow_v1_gameflow-phase".to_string(),
+            uri: "/lol-gameflow/v1/gameflow-phase".to_string(),
+            event_type: "Update".to_string(),
+            data,
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_returns_recorded_events_in_order() {
+        let dir = std::env::temp_dir().join(format!("reveal-recording-{}", now_ms()));
+        let mut recorder = EventRecorder::create(&dir).await.unwrap();
+
+        for phase in ["Lobby", "ReadyCheck", "ChampSelect"] {
+            recorder.record(&event(json!(phase))).await.unwrap();
+        }
+
+        let mut replay = EventReplay::open(recorder.path(), 0.0).await.unwrap();
+        let mut phases = Vec::new();
+        while let Some(event) = replay.next().await {
+            phases.push(event.data);
+        }
+
+        assert_eq!(
+            phases,
+            vec![json!("Lobby"), json!("ReadyCheck"), json!("ChampSelect")]
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
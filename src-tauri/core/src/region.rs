@@ -0,0 +1,290 @@
+use crate::error::{lcu_response, parse_response, CommandError};
+use crate::lcu::LcuClient;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionInfo {
+    pub locale: String,
+    pub region: String,
+    pub web_language: String,
+    pub web_region: String,
+}
+
+/// Regional routing value of the Riot API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoutingCluster {
+    Americas,
+    Europe,
+    Asia,
+    Sea,
+}
+
+impl RoutingCluster {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoutingCluster::Americas => "americas",
+            RoutingCluster::Europe => "europe",
+            RoutingCluster::Asia => "asia",
+            RoutingCluster::Sea => "sea",
+        }
+    }
+}
+
+/// One game server and how every site we link to names it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Shard {
+    /// The short name players know, e.g. `EUNE`.
+    pub name: &'static str,
+    pub platform_id: &'static str,
+    pub cluster: RoutingCluster,
+    /// Other spellings the client reports in `region` or `webRegion`.
+    pub aliases: &'static [&'static str],
+    pub opgg: &'static str,
+    pub deeplol: &'static str,
+    pub ugg: &'static str,
+    pub tracker: &'static str,
+}
+
+macro_rules! shard {
+    ($name:literal, $platform:literal, $cluster:ident, [$($alias:literal),*],
+     $opgg:literal, $deeplol:literal, $ugg:literal, $tracker:literal) => {
+        Shard {
+            name: $name,
+            platform_id: $platform,
+            cluster: RoutingCluster::$cluster,
+            aliases: &[$($alias),*],
+            opgg: $opgg,
+            deeplol: $deeplol,
+            ugg: $ugg,
+            tracker: $tracker,
+        }
+    };
+}
+
+/// Every live shard. PH2 and TH2 were merged into SG2, older clients still reporting them are
+/// routed to it through its aliases.
+#[rustfmt::skip]
+pub const SHARDS: &[Shard] = &[
+    //     name    platform cluster    aliases                      opgg    deeplol ugg     tracker
+    shard!("NA",   "NA1",   Americas, [],                          "na",   "NA",   "na1",  "NA"),
+    shard!("BR",   "BR1",   Americas, [],                          "br",   "BR",   "br1",  "BR"),
+    shard!("LAN",  "LA1",   Americas, [],                          "lan",  "LAN",  "la1",  "LAN"),
+    shard!("LAS",  "LA2",   Americas, [],                          "las",  "LAS",  "la2",  "LAS"),
+    shard!("EUW",  "EUW1",  Europe,   [],                          "euw",  "EUW",  "euw1", "EUW"),
+    shard!("EUNE", "EUN1",  Europe,   ["EUN"],                     "eune", "EUNE", "eun1", "EUNE"),
+    shard!("TR",   "TR1",   Europe,   [],                          "tr",   "TR",   "tr1",  "TR"),
+    shard!("RU",   "RU",    Europe,   [],                          "ru",   "RU",   "ru",   "RU"),
+    shard!("ME",   "ME1",   Europe,   [],                          "me",   "ME",   "me1",  "ME"),
+    shard!("KR",   "KR",    Asia,     [],                          "kr",   "KR",   "kr",   "KR"),
+    shard!("JP",   "JP1",   Asia,     [],                          "jp",   "JP",   "jp1",  "JP"),
+    shard!("OCE",  "OC1",   Sea,      ["OC"],                      "oce",  "OCE",  "oc1",  "OCE"),
+    shard!("SG",   "SG2",   Sea,      ["PH", "PH2", "TH", "TH2"],  "sg",   "SG",   "sg2",  "SG"),
+    shard!("TW",   "TW2",   Sea,      [],                          "tw",   "TW",   "tw2",  "TW"),
+    shard!("VN",   "VN2",   Sea,      [],                          "vn",   "VN",   "vn2",  "VN"),
+];
+
+impl Shard {
+    /// Finds a shard by name, platform id or alias, ignoring case.
+    pub fn find(value: &str) -> Option<&'static Shard> {
+        let value = value.trim();
+        SHARDS.iter().find(|shard| {
+            std::iter::once(shard.name)
+                .chain(std::iter::once(shard.platform_id))
+                .chain(shard.aliases.iter().copied())
+                .any(|known| known.eq_ignore_ascii_case(value))
+        })
+    }
+
+    /// Prefers `webRegion`, the client fills both but older builds left it empty.
+    pub fn from_region_info(region_info: &RegionInfo) -> Option<&'static Shard> {
+        Shard::find(&region_info.web_region).or_else(|| Shard::find(&region_info.region))
+    }
+
+    /// The region slug of a built-in multi search provider.
+    pub fn provider_slug(&self, provider: &str) -> Option<&'static str> {
+        match provider {
+            "opgg" => Some(self.opgg),
+            "deeplol" => Some(self.deeplol),
+            "ugg" => Some(self.ugg),
+            "tracker" => Some(self.tracker),
+            _ => None,
+        }
+    }
+}
+
+/// The region the client is logged into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Region {
+    Shard(&'static Shard),
+    /// Not in `SHARDS`, like PBE, passed on the way the client names it.
+    Unlisted(String),
+}
+
+impl Region {
+    pub fn from_region_info(region_info: &RegionInfo) -> Self {
+        if let Some(shard) = Shard::from_region_info(region_info) {
+            return Region::Shard(shard);
+        }
+
+        let region = match region_info.web_region.trim() {
+            "" => region_info.region.trim(),
+            web_region => web_region,
+        };
+        Region::Unlisted(region.to_string())
+    }
+
+    /// How `provider` names the region, the shard name if it has no slug of its own.
+    pub fn slug(&self, provider: &str) -> &str {
+        match self {
+            Region::Shard(shard) => shard.provider_slug(provider).unwrap_or(shard.name),
+            Region::Unlisted(region) => region,
+        }
+    }
+
+    pub fn platform_id(&self) -> &str {
+        match self {
+            Region::Shard(shard) => shard.platform_id,
+            Region::Unlisted(region) => region,
+        }
+    }
+
+    /// `None` for unlisted regions, there's no telling where they route.
+    pub fn cluster(&self) -> Option<RoutingCluster> {
+        match self {
+            Region::Shard(shard) => Some(shard.cluster),
+            Region::Unlisted(_) => None,
+        }
+    }
+}
+
+/// Reads the region the client is logged into from `/riotclient/region-locale`.
+pub async fn get_region(app_client: &LcuClient) -> Result<Region, CommandError> {
+    let region_info: RegionInfo = parse_response(lcu_response(
+        app_client
+            .get("/riotclient/region-locale".to_string())
+            .await,
+    )?)?;
+
+    let region = Region::from_region_info(&region_info);
+    if let Region::Unlisted(name) = &region {
+        println!("Unknown region {}, using it as is", name);
+    }
+
+    Ok(region)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region_info(region: &str, web_region: &str) -> RegionInfo {
+        RegionInfo {
+            locale: "en_US".to_string(),
+            region: region.to_string(),
+            web_language: "en".to_string(),
+            web_region: web_region.to_string(),
+        }
+    }
+
+    #[test]
+    fn every_shard_maps_to_its_platform_cluster_and_slugs() {
+        // (region, webRegion, platform, cluster, opgg, deeplol, ugg, tracker)
+        let expected = [
+            ("NA", "NA", "NA1", "americas", "na", "NA", "na1", "NA"),
+            ("BR", "BR", "BR1", "americas", "br", "BR", "br1", "BR"),
+            ("LA1", "LAN", "LA1", "americas", "lan", "LAN", "la1", "LAN"),
+            ("LA2", "LAS", "LA2", "americas", "las", "LAS", "la2", "LAS"),
+            ("EUW", "EUW", "EUW1", "europe", "euw", "EUW", "euw1", "EUW"),
+            (
+                "EUNE", "EUNE", "EUN1", "europe", "eune", "EUNE", "eun1", "EUNE",
+            ),
+            ("TR", "TR", "TR1", "europe", "tr", "TR", "tr1", "TR"),
+            ("RU", "RU", "RU", "europe", "ru", "RU", "ru", "RU"),
+            ("ME1", "ME", "ME1", "europe", "me", "ME", "me1", "ME"),
+            ("KR", "KR", "KR", "asia", "kr", "KR", "kr", "KR"),
+            ("JP", "JP", "JP1", "asia", "jp", "JP", "jp1", "JP"),
+            ("OC1", "OCE", "OC1", "sea", "oce", "OCE", "oc1", "OCE"),
+            ("SG2", "SG2", "SG2", "sea", "sg", "SG", "sg2", "SG"),
+            ("TW2", "TW", "TW2", "sea", "tw", "TW", "tw2", "TW"),
+            ("VN2", "VN", "VN2", "sea", "vn", "VN", "vn2", "VN"),
+        ];
+        assert_eq!(expected.len(), SHARDS.len());
+
+        for (region, web_region, platform, cluster, opgg, deeplol, ugg, tracker) in expected {
+            let shard = Shard::from_region_info(&region_info(region, web_region))
+                .unwrap_or_else(|| panic!("no shard for {}/{}", region, web_region));
+
+            assert_eq!(shard.platform_id, platform, "{}", web_region);
+            assert_eq!(shard.cluster.as_str(), cluster, "{}", web_region);
+            assert_eq!(shard.provider_slug("opgg"), Some(opgg), "{}", web_region);
+            assert_eq!(
+                shard.provider_slug("deeplol"),
+                Some(deeplol),
+                "{}",
+                web_region
+            );
+            assert_eq!(shard.provider_slug("ugg"), Some(ugg), "{}", web_region);
+            assert_eq!(
+                shard.provider_slug("tracker"),
+                Some(tracker),
+                "{}",
+                web_region
+            );
+
+            // Either field alone is enough.
+            assert_eq!(
+                Shard::from_region_info(&region_info(region, "")),
+                Some(shard)
+            );
+            assert_eq!(
+                Shard::from_region_info(&region_info("", web_region)),
+                Some(shard)
+            );
+        }
+    }
+
+    #[test]
+    fn merged_shards_route_to_sg2() {
+        for region in ["PH", "PH2", "TH", "TH2"] {
+            let shard = Shard::from_region_info(&region_info(region, region)).unwrap();
+
+            assert_eq!(shard.platform_id, "SG2", "{}", region);
+            assert_eq!(shard.cluster, RoutingCluster::Sea, "{}", region);
+            assert_eq!(shard.provider_slug("opgg"), Some("sg"), "{}", region);
+            assert_eq!(shard.provider_slug("ugg"), Some("sg2"), "{}", region);
+        }
+    }
+
+    #[test]
+    fn lookup_ignores_case_and_platform_ids() {
+        assert_eq!(Shard::find("euw1").unwrap().name, "EUW");
+        assert_eq!(Shard::find("eun1").unwrap().name, "EUNE");
+        assert_eq!(Shard::find("oce").unwrap().name, "OCE");
+        assert_eq!(Shard::find(" sg2 ").unwrap().name, "SG");
+    }
+
+    #[test]
+    fn unknown_regions_are_not_guessed() {
+        assert_eq!(Shard::find("PBE"), None);
+        assert_eq!(Shard::find(""), None);
+        assert_eq!(Shard::find("custom").map(|s| s.name), None);
+    }
+
+    #[test]
+    fn unlisted_regions_pass_through() {
+        let region = Region::from_region_info(&region_info("PBE", "PBE"));
+        assert_eq!(region, Region::Unlisted("PBE".to_string()));
+        assert_eq!(region.slug("opgg"), "PBE");
+        assert_eq!(region.platform_id(), "PBE");
+        assert_eq!(region.cluster(), None);
+
+        let region = Region::from_region_info(&region_info("XX1", ""));
+        assert_eq!(region, Region::Unlisted("XX1".to_string()));
+
+        let region = Region::from_region_info(&region_info("EUW", "EUW"));
+        assert_eq!(region.slug("opgg"), "euw");
+        assert_eq!(region.cluster(), Some(RoutingCluster::Europe));
+    }
+}
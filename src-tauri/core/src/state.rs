@@ -0,0 +1,282 @@
+use crate::{
+    champ_select::{handle_champ_select_end, handle_champ_select_start},
+    dodge::cancel_dodge,
+    end_game::handle_end_game_start_,
+    lcu::LcuClient,
+    notifications::{notify, NotifyEvent},
+    ready_check::{cancel_ready_check, handle_ready_check},
+    Host, ManagedGameflowState,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Phases reported by `/lol-gameflow/v1/gameflow-phase`.
+///
+/// Anything the client sends that we don't know about yet ends up in `Unknown` so a new
+/// Riot phase never breaks parsing.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameflowPhase {
+    None,
+    Lobby,
+    Matchmaking,
+    CheckedIntoTournament,
+    ReadyCheck,
+    ChampSelect,
+    GameStart,
+    FailedToLaunch,
+    InProgress,
+    Reconnect,
+    WaitingForStats,
+    PreEndOfGame,
+    EndOfGame,
+    TerminatedInError,
+    Unknown(String),
+}
+
+impl GameflowPhase {
+    pub fn as_str(&self) -> &str {
+        match self {
+            GameflowPhase::None => "None",
+            GameflowPhase::Lobby => "Lobby",
+            GameflowPhase::Matchmaking => "Matchmaking",
+            GameflowPhase::CheckedIntoTournament => "CheckedIntoTournament",
+            GameflowPhase::ReadyCheck => "ReadyCheck",
+            GameflowPhase::ChampSelect => "ChampSelect",
+            GameflowPhase::GameStart => "GameStart",
+            GameflowPhase::FailedToLaunch => "FailedToLaunch",
+            GameflowPhase::InProgress => "InProgress",
+            GameflowPhase::Reconnect => "Reconnect",
+            GameflowPhase::WaitingForStats => "WaitingForStats",
+            GameflowPhase::PreEndOfGame => "PreEndOfGame",
+            GameflowPhase::EndOfGame => "EndOfGame",
+            GameflowPhase::TerminatedInError => "TerminatedInError",
+            GameflowPhase::Unknown(phase) => phase,
+        }
+    }
+}
+
+impl From<&str> for GameflowPhase {
+    fn from(phase: &str) -> Self {
+        match phase {
+            "None" => GameflowPhase::None,
+            "Lobby" => GameflowPhase::Lobby,
+            "Matchmaking" => GameflowPhase::Matchmaking,
+            "CheckedIntoTournament" => GameflowPhase::CheckedIntoTournament,
+            "ReadyCheck" => GameflowPhase::ReadyCheck,
+            "ChampSelect" => GameflowPhase::ChampSelect,
+            "GameStart" => GameflowPhase::GameStart,
+            "FailedToLaunch" => GameflowPhase::FailedToLaunch,
+            "InProgress" => GameflowPhase::InProgress,
+            "Reconnect" => GameflowPhase::Reconnect,
+            "WaitingForStats" => GameflowPhase::WaitingForStats,
+            "PreEndOfGame" => GameflowPhase::PreEndOfGame,
+            "EndOfGame" => GameflowPhase::EndOfGame,
+            "TerminatedInError" => GameflowPhase::TerminatedInError,
+            other => GameflowPhase::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for GameflowPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for GameflowPhase {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for GameflowPhase {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let phase = String::deserialize(deserializer)?;
+        Ok(GameflowPhase::from(phase.as_str()))
+    }
+}
+
+/// A change from one phase to another, emitted to the frontend as `gameflow_transition`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameflowTransition {
+    pub from: Option<GameflowPhase>,
+    pub to: GameflowPhase,
+}
+
+impl GameflowTransition {
+    pub fn is_leaving(&self, phase: &GameflowPhase) -> bool {
+        self.from.as_ref() == Some(phase)
+    }
+}
+
+/// Tracks the current and previous phase so a phase delivered twice (once from the initial
+/// poll and once from the websocket, or repeated by the client) only triggers automations once.
+#[derive(Debug, Default)]
+pub struct GameflowStateMachine {
+    current: Option<GameflowPhase>,
+    previous: Option<GameflowPhase>,
+}
+
+impl GameflowStateMachine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves to `next`, returning `None` if we are already in that phase.
+    pub fn transition(&mut self, next: GameflowPhase) -> Option<GameflowTransition> {
+        if self.current.as_ref() == Some(&next) {
+            return None;
+        }
+
+        self.previous = self.current.replace(next.clone());
+
+        Some(GameflowTransition {
+            from: self.previous.clone(),
+            to: next,
+        })
+    }
+
+    pub fn current(&self) -> Option<&GameflowPhase> {
+        self.current.as_ref()
+    }
+
+    /// Forgets everything, used when the client connection is (re)established.
+    pub fn reset(&mut self) {
+        self.current = None;
+        self.previous = None;
+    }
+}
+
+pub async fn get_gameflow_state(remoting_client: &LcuClient) -> Option<GameflowPhase> {
+    let state = remoting_client
+        .get("/lol-gameflow/v1/gameflow-phase".to_string())
+        .await
+        .ok()?;
+    serde_json::from_value(state).ok()
+}
+
+pub async fn handle_client_state<H: Host>(
+    client_state: GameflowPhase,
+    host: &H,
+    remoting_client: &LcuClient,
+    app_client: &LcuClient,
+) {
+    let transition = {
+        let gameflow = host.state::<ManagedGameflowState>();
+        let mut gameflow = gameflow.0.lock().await;
+        gameflow.transition(client_state)
+    };
+
+    let Some(transition) = transition else {
+        return;
+    };
+
+    println!(
+        "Client State Update: {} -> {}",
+        transition
+            .from
+            .as_ref()
+            .map(GameflowPhase::as_str)
+            .unwrap_or("Disconnected"),
+        transition.to
+    );
+
+    if transition.is_leaving(&GameflowPhase::ReadyCheck) {
+        cancel_ready_check(host, "ready check ended").await;
+    }
+
+    if transition.is_leaving(&GameflowPhase::ChampSelect) {
+        handle_champ_select_end(host).await;
+        cancel_dodge(host, "champ select ended").await;
+    }
+
+    match transition.to {
+        GameflowPhase::ChampSelect => {
+            notify(
+                host,
+                NotifyEvent::ChampSelect,
+                "Champ select started",
+                "Your team is in champ select",
+            )
+            .await;
+            handle_champ_select_start(app_client, host).await;
+        }
+
+        GameflowPhase::ReadyCheck => {
+            notify(
+                host,
+                NotifyEvent::ReadyCheck,
+                "Match found",
+                "A ready check is waiting for you",
+            )
+            .await;
+            handle_ready_check(host, remoting_client).await;
+        }
+
+        // Both phases lead to the stats block, only run once per game.
+        GameflowPhase::PreEndOfGame | GameflowPhase::EndOfGame
+            if !transition.is_leaving(&GameflowPhase::PreEndOfGame) =>
+        {
+            let cloned_host = host.clone();
+            let cloned_app_client = app_client.clone();
+            let cloned_remoting = remoting_client.clone();
+
+            tokio::spawn(async move {
+                handle_end_game_start_(cloned_host, cloned_app_client, cloned_remoting).await;
+            });
+        }
+
+        _ => {}
+    }
+
+    host.emit("gameflow_transition", &transition);
+    host.emit("client_state_update", &transition.to);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_phase_is_ignored() {
+        let mut gameflow = GameflowStateMachine::new();
+
+        assert!(gameflow.transition(GameflowPhase::ReadyCheck).is_some());
+        assert!(gameflow.transition(GameflowPhase::ReadyCheck).is_none());
+    }
+
+    #[test]
+    fn transitions_track_the_previous_phase() {
+        let mut gameflow = GameflowStateMachine::new();
+
+        let first = gameflow.transition(GameflowPhase::Lobby).unwrap();
+        assert_eq!(first.from, None);
+        assert_eq!(first.to, GameflowPhase::Lobby);
+
+        let second = gameflow.transition(GameflowPhase::Matchmaking).unwrap();
+        assert_eq!(second.from, Some(GameflowPhase::Lobby));
+        assert_eq!(second.to, GameflowPhase::Matchmaking);
+        assert!(second.is_leaving(&GameflowPhase::Lobby));
+        assert!(!second.is_leaving(&GameflowPhase::Matchmaking));
+    }
+
+    #[test]
+    fn reset_replays_the_current_phase() {
+        let mut gameflow = GameflowStateMachine::new();
+        gameflow.transition(GameflowPhase::ChampSelect);
+
+        // After a reconnect the client reports the phase we were already in.
+        gameflow.reset();
+        let transition = gameflow.transition(GameflowPhase::ChampSelect).unwrap();
+        assert_eq!(transition.from, None);
+        assert_eq!(transition.to, GameflowPhase::ChampSelect);
+    }
+
+    #[test]
+    fn unknown_phases_round_trip() {
+        let phase: GameflowPhase = serde_json::from_str("\"SomethingNew\"").unwrap();
+        assert_eq!(phase, GameflowPhase::Unknown("SomethingNew".to_string()));
+        assert_eq!(serde_json::to_string(&phase).unwrap(), "\"SomethingNew\"");
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{
+    error::CommandError,
+    lobby::{get_lobby_info, Lobby},
+    region::{get_region, Region},
+    utils::multisearch_link,
+    AppConfig, Config, Host, LCU,
+};
+use serde::{Deserialize, Serialize};
+
+/// How `copy_team` writes the team.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TeamFormat {
+    /// One Riot ID per line.
+    #[default]
+    Plain,
+    /// A bullet list with the multi search link underneath.
+    Markdown,
+    /// Riot IDs in inline code so they copy cleanly, the link wrapped in `<>` so it doesn't embed.
+    Discord,
+    /// Only the multi search link.
+    Link,
+}
+
+pub fn format_team(lobby: &Lobby, format: TeamFormat, link: &str) -> String {
+    let riot_ids = lobby
+        .participants
+        .iter()
+        .map(|p| format!("{}#{}", p.game_name, p.game_tag));
+
+    match format {
+        TeamFormat::Plain => riot_ids.collect::<Vec<_>>().join("\n"),
+        TeamFormat::Markdown => {
+            let list = riot_ids
+                .map(|id| format!("- {}", id))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("{}\n\n[Multi search]({})", list, link)
+        }
+        TeamFormat::Discord => {
+            let list = riot_ids
+                .map(|id| format!("`{}`", id))
+                .collect::<Vec<_>>()
+                .join(" · ");
+            format!("{}\n<{}>", list, link)
+        }
+        TeamFormat::Link => link.to_string(),
+    }
+}
+
+/// Formats the team and puts it on the clipboard, returning the copied text.
+pub fn copy_team_text<H: Host>(
+    host: &H,
+    lobby: &Lobby,
+    region: &Region,
+    config: &Config,
+    format: TeamFormat,
+) -> Result<String, CommandError> {
+    let link = multisearch_link(lobby, region, config)?;
+    let text = format_team(lobby, format, &link);
+
+    host.copy_text(text.clone())?;
+
+    Ok(text)
+}
+
+/// Copies the current champ select team, in `format` or the configured one.
+pub async fn copy_current_team<H: Host>(
+    host: &H,
+    format: Option<TeamFormat>,
+) -> Result<String, CommandError> {
+    let app_client = {
+        let lcu_state = host.state::<LCU>();
+        let lcu_state = lcu_state.0.lock().await;
+        lcu_state.connected_clients()?.app
+    };
+
+    let config = {
+        let cfg = host.state::<AppConfig>();
+        let cfg = cfg.0.lock().await;
+        cfg.clone()
+    };
+
+    let team = get_lobby_info(&app_client).await?;
+    if team.participants.is_empty() {
+        return Err(CommandError::NotInChampSelect);
+    }
+
+    let region = get_region(&app_client).await?;
+    copy_team_text(
+        host,
+        &team,
+        &region,
+        &config,
+        format.unwrap_or(config.team_format),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lobby::Participant;
+
+    fn team() -> Lobby {
+        Lobby {
+            participants: [("Faker", "KR1"), ("Keria", "T1")]
+                .iter()
+                .map(|(name, tag)| Participant {
+                    cid: String::new(),
+                    game_name: name.to_string(),
+                    game_tag: tag.to_string(),
+                    muted: false,
+                    name: name.to_string(),
+                    pid: String::new(),
+                    puuid: String::new(),
+                    region: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn formats_the_team() {
+        let link = "https://example.gg/kr/Faker%23KR1";
+
+        assert_eq!(
+            format_team(&team(), TeamFormat::Plain, link),
+            "Faker#KR1\nKeria#T1"
+        );
+        assert_eq!(
+            format_team(&team(), TeamFormat::Markdown, link),
+            "- Faker#KR1\n- Keria#T1\n\n[Multi search](https://example.gg/kr/Faker%23KR1)"
+        );
+        assert_eq!(
+            format_team(&team(), TeamFormat::Discord, link),
+            "`Faker#KR1` · `Keria#T1`\n<https://example.gg/kr/Faker%23KR1>"
+        );
+        assert_eq!(format_team(&team(), TeamFormat::Link, link), link);
+    }
+}
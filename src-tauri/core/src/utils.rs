@@ -0,0 +1,100 @@
+use crate::{
+    error::CommandError, lobby::Lobby, multisearch::ProviderRegistry, region::Region, Config,
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use serde_json::Value;
+
+/// Falls back to the default when a field has an unexpected shape, so a single changed field
+/// in a Riot payload doesn't fail the whole parse. Use with `#[serde(default)]`.
+pub fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(T::deserialize(value).unwrap_or_default())
+}
+
+/// [`lenient`] for lists, drops only the elements that don't parse instead of the whole list.
+pub fn lenient_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(lenient_elements(value))
+}
+
+/// [`lenient`] for lists where the position matters, like item slots: an element that doesn't
+/// parse becomes the default instead of shifting the ones after it.
+pub fn lenient_slots<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Array(elements) => Ok(elements
+            .into_iter()
+            .map(|element| T::deserialize(element).unwrap_or_default())
+            .collect()),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// The elements of `value` that parse as `T`, nothing when it isn't an array.
+pub fn lenient_elements<T: DeserializeOwned>(value: Value) -> Vec<T> {
+    match value {
+        Value::Array(elements) => elements
+            .into_iter()
+            .filter_map(|element| T::deserialize(element).ok())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The team on the configured multi search site.
+pub fn multisearch_link(
+    lobby: &Lobby,
+    region: &Region,
+    config: &Config,
+) -> Result<String, CommandError> {
+    let registry = ProviderRegistry::from_config(config);
+    Ok(registry
+        .get(&config.multi_provider)?
+        .link(&lobby.participants, region))
+}
+
+/// Opens the team on the configured multi search site.
+pub fn display_champ_select(
+    lobby: &Lobby,
+    region: &Region,
+    config: &Config,
+) -> Result<(), CommandError> {
+    if lobby.participants.is_empty() {
+        return Ok(());
+    }
+
+    let mut team_string = String::new();
+    for summoner in lobby.participants.iter() {
+        let participant = format!(
+            "{}#{} ({})",
+            summoner.game_name, summoner.game_tag, summoner.name
+        );
+        team_string.push_str(&participant);
+        if summoner.name != lobby.participants.last().unwrap().name {
+            team_string.push_str(", ");
+        }
+    }
+
+    println!("Team: {}", team_string);
+    let link = multisearch_link(lobby, region, config)?;
+
+    match open::that(&link) {
+        Ok(_) => {}
+        Err(_) => {
+            println!("Failed to open link in browser");
+        }
+    }
+
+    Ok(())
+}
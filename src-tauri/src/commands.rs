@@ -1,8 +1,20 @@
-use crate::{
-    champ_select::ChampSelectSession, lobby::get_lobby_info, region::RegionInfo,
-    utils::display_champ_select, AppConfig, Config, ManagedDodgeState, LCU,
+use crate::tray::refresh_tray;
+use reveal_core::{
+    champ_select::ChampSelectSession,
+    config::{activate_profile, update_config, ProfileSummary},
+    dodge::{cancel_dodge, send_dodge},
+    dodge_history::{dodge_status, record_dodge, DodgeKind, PenaltyStatus},
+    end_game::{self, EndOfGameStats, ReportQueue, ReportRequest},
+    error::{lcu_response, parse_response, CommandError},
+    lobby::get_lobby_info,
+    match_db::{query_db, GameRecord, TeammateEncounter},
+    multisearch::{ProviderInfo, ProviderRegistry},
+    region::get_region,
+    team_export::{copy_current_team, TeamFormat},
+    utils::display_champ_select,
+    AppConfig, Config, ConfigProfiles, ManagedDodgeState, ManagedReportState, LCU,
 };
-use shaco::rest::{LCUClientInfo, RESTClient};
+use shaco::rest::LCUClientInfo;
 use tauri::{AppHandle, Manager};
 
 #[tauri::command]
@@ -10,125 +22,266 @@ pub async fn app_ready(
     app_handle: AppHandle,
     lcu: tauri::State<'_, LCU>,
     cfg: tauri::State<'_, AppConfig>,
-) -> Result<Config, ()> {
+    profiles: tauri::State<'_, ConfigProfiles>,
+) -> Result<Config, CommandError> {
     println!("App Ready!");
+    let profile = profiles.0.lock().await.active_profile.clone();
     let lcu = lcu.0.lock().await;
     let cfg = cfg.0.lock().await;
 
     println!("LCU State: {}", lcu.connected);
-    println!("Config: {:?}", cfg);
+    println!("Config loaded (profile {})", profile);
 
-    app_handle
-        .emit_all("lcu_state_update", lcu.connected)
-        .unwrap();
+    // Through the host so the tray hears about it too.
+    reveal_core::Host::emit(&app_handle, "lcu_state_update", lcu.connected);
 
     Ok(cfg.clone())
 }
 
 #[tauri::command]
-pub async fn get_lcu_state(lcu: tauri::State<'_, LCU>) -> Result<bool, ()> {
+pub async fn get_lcu_state(lcu: tauri::State<'_, LCU>) -> Result<bool, CommandError> {
     let lcu = lcu.0.lock().await;
     Ok(lcu.connected)
 }
 
 #[tauri::command]
-pub async fn get_config(cfg: tauri::State<'_, AppConfig>) -> Result<Config, ()> {
+pub async fn get_config(cfg: tauri::State<'_, AppConfig>) -> Result<Config, CommandError> {
     let cfg = cfg.0.lock().await;
     Ok(cfg.clone())
 }
 
 #[tauri::command]
-pub async fn set_config(
-    cfg: tauri::State<'_, AppConfig>,
-    new_cfg: Config,
-    app_handle: AppHandle,
-) -> Result<(), ()> {
+pub async fn set_config(app_handle: AppHandle, new_cfg: Config) -> Result<(), CommandError> {
     println!("Setting Config: {:?}", new_cfg);
-    let mut cfg = cfg.0.lock().await;
-    *cfg = new_cfg;
+    update_config(&app_handle, new_cfg).await
+}
 
-    // Save config to disk
-    let cfg_folder = app_handle.path_resolver().app_config_dir().unwrap();
-    let cfg_path = cfg_folder.join("config.json");
-    let cfg_json = serde_json::to_string(&cfg.clone()).unwrap();
-    tokio::fs::write(&cfg_path, cfg_json).await.unwrap();
+#[tauri::command]
+pub async fn list_profiles(
+    profiles: tauri::State<'_, ConfigProfiles>,
+) -> Result<Vec<ProfileSummary>, CommandError> {
+    let profiles = profiles.0.lock().await;
+    Ok(profiles.summaries())
+}
 
-    Ok(())
+#[tauri::command]
+pub async fn switch_profile(app_handle: AppHandle, name: String) -> Result<Config, CommandError> {
+    activate_profile(&app_handle, &name).await
 }
 
+/// Saves the current settings as profile `name`, bound to the logged in account if `bind_account`.
+#[tauri::command]
+pub async fn save_profile(
+    app_handle: AppHandle,
+    name: String,
+    bind_account: bool,
+) -> Result<Vec<ProfileSummary>, CommandError> {
+    let puuid = if bind_account {
+        let lcu_state = app_handle.state::<LCU>();
+        let remoting_client = lcu_state.0.lock().await.connected_clients()?.remoting;
+
+        let summoner = lcu_response(
+            remoting_client
+                .get("/lol-summoner/v1/current-summoner".to_string())
+                .await,
+        )?;
+        let puuid = summoner
+            .get("puuid")
+            .and_then(|puuid| puuid.as_str())
+            .ok_or_else(|| {
+                CommandError::InvalidResponse("current summoner has no puuid".to_string())
+            })?;
+        Some(puuid.to_string())
+    } else {
+        None
+    };
+
+    let config = app_handle.state::<AppConfig>().0.lock().await.clone();
+
+    let profiles = app_handle.state::<ConfigProfiles>();
+    let summaries = {
+        let mut profiles = profiles.0.lock().await;
+        profiles.save_profile(&name, config, puuid);
+        profiles.save().await?;
+        profiles.summaries()
+    };
+
+    refresh_tray(&app_handle).await;
+    Ok(summaries)
+}
 
 #[tauri::command]
-pub async fn open_opgg_link(app_handle: AppHandle) -> Result<(), ()> {
+pub async fn open_opgg_link(app_handle: AppHandle) -> Result<(), CommandError> {
     let lcu_state = app_handle.state::<LCU>();
-    let lcu_state = lcu_state.0.lock().await;
-    let app_client = RESTClient::new(lcu_state.data.clone().unwrap(), false).unwrap();
-
-    let config = app_handle.state::<AppConfig>();
-    let config = config.0.lock().await;
-
-    let team = get_lobby_info(&app_client).await;
-    let region_info: RegionInfo = serde_json::from_value(
-        app_client
-            .get("/riotclient/region-locale".to_string())
-            .await
-            .unwrap(),
-    )
-    .unwrap();
+    let app_client = lcu_state.0.lock().await.connected_clients()?.app;
 
-    let region = match region_info.web_region.as_str() {
-        "SG2" => "SG",
-        _ => &region_info.web_region,
-    };
+    let config = app_handle.state::<AppConfig>().0.lock().await.clone();
 
-    display_champ_select(&team, region, &config.multi_provider);
+    let team = get_lobby_info(&app_client).await?;
+    let region = get_region(&app_client).await?;
 
+    display_champ_select(&team, &region, &config)
+}
+
+/// Copies the team in `format`, or the configured format, and returns the copied text.
+#[tauri::command]
+pub async fn copy_team(
+    app_handle: AppHandle,
+    format: Option<TeamFormat>,
+) -> Result<String, CommandError> {
+    copy_current_team(&app_handle, format).await
+}
+
+/// Shows a sample notification so the user can check their notification daemon and sound.
+#[tauri::command]
+pub async fn test_notification(app_handle: AppHandle) -> Result<(), CommandError> {
+    let sound = {
+        let cfg = app_handle.state::<AppConfig>();
+        let cfg = cfg.0.lock().await;
+        cfg.notifications.sound
+    };
+
+    reveal_core::Host::notify(&app_handle, "Reveal", "Notifications are working", sound);
     Ok(())
 }
 
 #[tauri::command]
-pub async fn get_lcu_info(lcu: tauri::State<'_, LCU>) -> Result<LCUClientInfo, ()> {
+pub async fn list_providers(
+    cfg: tauri::State<'_, AppConfig>,
+) -> Result<Vec<ProviderInfo>, CommandError> {
+    let cfg = cfg.0.lock().await;
+    Ok(ProviderRegistry::from_config(&cfg).list())
+}
+
+/// The latest recorded games, newest first.
+#[tauri::command]
+pub async fn get_recent_games(
+    app_handle: AppHandle,
+    limit: Option<u32>,
+) -> Result<Vec<GameRecord>, CommandError> {
+    query_db(&app_handle, move |db| db.recent_games(limit.unwrap_or(20))).await
+}
+
+/// Players we had on our team in more than one recorded game.
+#[tauri::command]
+pub async fn get_teammate_encounters(
+    app_handle: AppHandle,
+    limit: Option<u32>,
+) -> Result<Vec<TeammateEncounter>, CommandError> {
+    query_db(&app_handle, move |db| {
+        db.teammate_encounters(limit.unwrap_or(50))
+    })
+    .await
+}
+
+/// Players from the last game up for review, `None` if report review is off or no game ended.
+#[tauri::command]
+pub async fn get_report_queue(
+    report_state: tauri::State<'_, ManagedReportState>,
+) -> Result<Option<ReportQueue>, CommandError> {
+    let report_state = report_state.0.lock().await;
+    Ok(report_state.queue.clone())
+}
+
+/// Stats of the last game that ended while Reveal was running.
+#[tauri::command]
+pub async fn get_last_game_stats(
+    report_state: tauri::State<'_, ManagedReportState>,
+) -> Result<Option<EndOfGameStats>, CommandError> {
+    let report_state = report_state.0.lock().await;
+    Ok(report_state.last_game.clone())
+}
+
+/// Reports one player from the review queue for the categories the user picked.
+#[tauri::command]
+pub async fn submit_report(
+    app_handle: AppHandle,
+    report: ReportRequest,
+) -> Result<ReportQueue, CommandError> {
+    let lcu_state = app_handle.state::<LCU>();
+    let remoting_client = lcu_state.0.lock().await.connected_clients()?.remoting;
+
+    end_game::submit_report(&app_handle, &remoting_client, report).await
+}
+
+#[tauri::command]
+pub async fn get_lcu_info(lcu: tauri::State<'_, LCU>) -> Result<LCUClientInfo, CommandError> {
     let lcu = lcu.0.lock().await;
-    Ok(lcu.data.clone().unwrap())
+    lcu.data.clone().ok_or(CommandError::NotConnected)
 }
 
 #[tauri::command]
-pub async fn dodge(app_handle: AppHandle) {
+pub async fn dodge(app_handle: AppHandle) -> Result<(), CommandError> {
     let lcu_state = app_handle.state::<LCU>();
-    let lcu_state = lcu_state.0.lock().await;
-    let remoting_client = RESTClient::new(lcu_state.data.clone().unwrap(), true).unwrap();
+    let remoting_client = lcu_state.0.lock().await.connected_clients()?.remoting;
+
+    // The session is gone once we've quit, grab the game id for the history first.
+    let game_id = lcu_response(
+        remoting_client
+            .get("/lol-champ-select/v1/session".to_string())
+            .await,
+    )
+    .ok()
+    .and_then(|session| session.get("gameId").and_then(|id| id.as_u64()));
 
     println!("Attempting to quit champ select...");
-    let _resp = remoting_client
-        .post(
-            "/lol-login/v1/session/invoke?destination=lcdsServiceProxy&method=call&args=[\"\",\"teambuilder-draft\",\"quitV2\",\"\"]".to_string(),
-            serde_json::json!({}),
-        )
-        .await
-        .unwrap();
+    send_dodge(&remoting_client).await?;
+
+    if let Some(game_id) = game_id {
+        let dodge_state = app_handle.state::<ManagedDodgeState>();
+        dodge_state.0.lock().await.last_dodge = Some(game_id);
+        record_dodge(&app_handle, &remoting_client, game_id, DodgeKind::Manual).await;
+    }
+
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn enable_dodge(app_handle: AppHandle) -> Result<(), ()> {
+pub async fn get_dodge_status(app_handle: AppHandle) -> Result<PenaltyStatus, CommandError> {
+    let remoting_client = {
+        let lcu_state = app_handle.state::<LCU>();
+        let lcu_state = lcu_state.0.lock().await;
+        lcu_state
+            .clients
+            .as_ref()
+            .map(|clients| clients.remoting.clone())
+    };
+
+    // The local history is still useful while the client is closed.
+    Ok(dodge_status(&app_handle, remoting_client.as_ref()).await)
+}
+
+/// Toggles the last second dodge for the current champ select.
+#[tauri::command]
+pub async fn enable_dodge(app_handle: AppHandle) -> Result<(), CommandError> {
+    let result = toggle_dodge(&app_handle).await;
+    refresh_tray(&app_handle).await;
+    result
+}
+
+async fn toggle_dodge(app_handle: &AppHandle) -> Result<(), CommandError> {
     let lcu_state = app_handle.state::<LCU>();
-    let lcu_state = lcu_state.0.lock().await;
-    let remoting_client = RESTClient::new(lcu_state.data.clone().unwrap(), true).unwrap();
+    let remoting_client = lcu_state.0.lock().await.connected_clients()?.remoting;
 
     let dodge_state = app_handle.state::<ManagedDodgeState>();
-    let mut dodge_state = dodge_state.0.lock().await;
-
-    if dodge_state.enabled.is_some() {
-        dodge_state.enabled = None;
+    let was_enabled = dodge_state.0.lock().await.enabled.take().is_some();
+    if was_enabled {
+        cancel_dodge(app_handle, "dodge disabled").await;
         return Ok(());
     }
 
-    let champ_select = serde_json::from_value::<ChampSelectSession>(
+    // Not holding the dodge state across the request, the scheduler needs it meanwhile.
+    let session = lcu_response(
         remoting_client
             .get("/lol-champ-select/v1/session".to_string())
-            .await
-            .unwrap(),
+            .await,
     )
-    .unwrap();
+    .map_err(|err| match err.status() {
+        Some(404) => CommandError::NotInChampSelect,
+        _ => err,
+    })?;
+    let champ_select = parse_response::<ChampSelectSession>(session)?;
 
-    dodge_state.enabled = Some(champ_select.game_id);
+    dodge_state.0.lock().await.enabled = Some(champ_select.game_id);
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,288 @@
+use crate::commands::{enable_dodge, open_opgg_link};
+use reveal_core::{
+    config::{activate_profile, update_config, ProfileSummary},
+    connection::ConnectionState,
+    state::GameflowPhase,
+    team_export::copy_current_team,
+    AppConfig, Config, ConfigProfiles, ManagedDodgeState, ManagedGameflowState, LCU,
+};
+use tauri::{
+    AppHandle, CustomMenuItem, Manager, Runtime, SystemTrayMenu, SystemTrayMenuItem,
+    SystemTraySubmenu,
+};
+use tauri_plugin_positioner::{Position, WindowExt};
+
+pub const TRAY_ID: &str = "main";
+
+const OPEN_REVEAL: &str = "open_reveal";
+const QUIT_REVEAL: &str = "quit_reveal";
+const AUTO_ACCEPT: &str = "auto_accept";
+const AUTO_OPEN: &str = "auto_open";
+const DODGE: &str = "dodge";
+const COPY_TEAM: &str = "copy_team";
+const OPEN_MULTI: &str = "open_multi";
+const PROFILE_ITEM_PREFIX: &str = "profile:";
+
+/// Everything the tray menu shows, collected from the managed state on every rebuild.
+#[derive(Debug, Default)]
+pub struct TrayStatus {
+    pub connection: ConnectionState,
+    pub phase: Option<GameflowPhase>,
+    pub auto_accept: bool,
+    pub auto_open: bool,
+    pub dodge_enabled: bool,
+    pub profiles: Vec<ProfileSummary>,
+}
+
+impl TrayStatus {
+    async fn collect<R: Runtime>(app_handle: &AppHandle<R>) -> Self {
+        let connection = app_handle.state::<LCU>().0.lock().await.connection_state;
+        let phase = {
+            let gameflow = app_handle.state::<ManagedGameflowState>();
+            let gameflow = gameflow.0.lock().await;
+            gameflow.current().cloned()
+        };
+        let (auto_accept, auto_open) = {
+            let cfg = app_handle.state::<AppConfig>();
+            let cfg = cfg.0.lock().await;
+            (cfg.auto_accept, cfg.auto_open)
+        };
+        let dodge_enabled = {
+            let dodge_state = app_handle.state::<ManagedDodgeState>();
+            let dodge_state = dodge_state.0.lock().await;
+            dodge_state.enabled.is_some()
+        };
+        let profiles = app_handle
+            .state::<ConfigProfiles>()
+            .0
+            .lock()
+            .await
+            .summaries();
+
+        Self {
+            connection,
+            phase,
+            auto_accept,
+            auto_open,
+            dodge_enabled,
+            profiles,
+        }
+    }
+
+    fn status_line(&self) -> String {
+        match (self.connection, &self.phase) {
+            (ConnectionState::Searching, _) => "League client not found".to_string(),
+            (ConnectionState::Authenticating, _) => "Connecting to the League client".to_string(),
+            (ConnectionState::Degraded, _) => "League client not responding, retrying".to_string(),
+            (ConnectionState::Disconnected, _) => "League client disconnected".to_string(),
+            (ConnectionState::Connected, None) => "Connected".to_string(),
+            (ConnectionState::Connected, Some(phase)) => format!("Connected: {}", phase),
+        }
+    }
+}
+
+fn item(id: &str, title: &str) -> CustomMenuItem {
+    CustomMenuItem::new(id.to_string(), title)
+}
+
+fn checkable(id: &str, title: &str, checked: bool) -> CustomMenuItem {
+    let item = item(id, title);
+    if checked {
+        item.selected()
+    } else {
+        item
+    }
+}
+
+pub fn tray_menu(status: &TrayStatus) -> SystemTrayMenu {
+    let mut menu = SystemTrayMenu::new()
+        .add_item(item("status", &status.status_line()).disabled())
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(item(OPEN_REVEAL, "Open Reveal"))
+        .add_item(checkable(AUTO_ACCEPT, "Auto Accept", status.auto_accept))
+        .add_item(checkable(AUTO_OPEN, "Auto Open Multi", status.auto_open));
+
+    if status.phase == Some(GameflowPhase::ChampSelect) {
+        menu = menu
+            .add_native_item(SystemTrayMenuItem::Separator)
+            .add_item(checkable(DODGE, "Last Second Dodge", status.dodge_enabled))
+            .add_item(item(COPY_TEAM, "Copy Team"))
+            .add_item(item(OPEN_MULTI, "Open Multi Search"));
+    }
+
+    if !status.profiles.is_empty() {
+        let profile_menu = status
+            .profiles
+            .iter()
+            .fold(SystemTrayMenu::new(), |menu, profile| {
+                let id = format!("{}{}", PROFILE_ITEM_PREFIX, profile.name);
+                menu.add_item(checkable(&id, &profile.name, profile.active))
+            });
+        menu = menu
+            .add_native_item(SystemTrayMenuItem::Separator)
+            .add_submenu(SystemTraySubmenu::new("Profiles", profile_menu));
+    }
+
+    menu.add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(item(QUIT_REVEAL, "Quit Reveal"))
+}
+
+/// Rebuilds the tray from the current state, apps without a tray (tests) are skipped.
+///
+/// Takes each lock on its own, so it must not be called while holding any of them.
+pub async fn refresh_tray<R: Runtime>(app_handle: &AppHandle<R>) {
+    let Some(tray) = app_handle.tray_handle_by_id(TRAY_ID) else {
+        return;
+    };
+
+    let status = TrayStatus::collect(app_handle).await;
+    let _ = tray.set_menu(tray_menu(&status));
+}
+
+/// Keeps the tray in sync with the client connection, the gameflow phase and the config.
+pub fn refresh_on_updates<R: Runtime>(app_handle: &AppHandle<R>) {
+    for event in [
+        "connection_state_update",
+        "lcu_state_update",
+        "client_state_update",
+        "config_update",
+    ] {
+        let handle = app_handle.clone();
+        app_handle.listen_global(event, move |_| {
+            let handle = handle.clone();
+            tauri::async_runtime::spawn(async move { refresh_tray(&handle).await });
+        });
+    }
+}
+
+pub fn show_window<R: Runtime>(app_handle: &AppHandle<R>) {
+    if let Some(win) = app_handle.get_window("main") {
+        let _ = win.show();
+        let _ = win.set_focus();
+        let _ = win.move_window(Position::TrayCenter);
+    }
+}
+
+/// Saves a config change made from the tray the same way `set_config` does.
+async fn toggle_config(app_handle: &AppHandle, toggle: impl FnOnce(&mut Config)) {
+    let mut config = app_handle.state::<AppConfig>().0.lock().await.clone();
+    toggle(&mut config);
+
+    if let Err(err) = update_config(app_handle, config).await {
+        println!("Failed to save config: {}", err);
+    }
+}
+
+pub fn handle_menu_click(app_handle: &AppHandle, id: &str) {
+    match id {
+        OPEN_REVEAL => show_window(app_handle),
+        QUIT_REVEAL => app_handle.exit(0),
+        _ => {
+            let app_handle = app_handle.clone();
+            let id = id.to_string();
+            tauri::async_runtime::spawn(async move { handle_menu_action(&app_handle, &id).await });
+        }
+    }
+}
+
+async fn handle_menu_action(app_handle: &AppHandle, id: &str) {
+    let result = match id {
+        AUTO_ACCEPT => {
+            toggle_config(app_handle, |cfg| cfg.auto_accept = !cfg.auto_accept).await;
+            Ok(())
+        }
+        AUTO_OPEN => {
+            toggle_config(app_handle, |cfg| cfg.auto_open = !cfg.auto_open).await;
+            Ok(())
+        }
+        DODGE => enable_dodge(app_handle.clone()).await,
+        COPY_TEAM => copy_current_team(app_handle, None).await.map(|_| ()),
+        OPEN_MULTI => open_opgg_link(app_handle.clone()).await,
+        id => match id.strip_prefix(PROFILE_ITEM_PREFIX) {
+            Some(name) => activate_profile(app_handle, name).await.map(|_| ()),
+            None => Ok(()),
+        },
+    };
+
+    if let Err(err) = result {
+        println!("Tray action {} failed: {}", id, err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::SystemTrayMenuEntry;
+
+    fn items(menu: &SystemTrayMenu) -> Vec<&CustomMenuItem> {
+        menu.items
+            .iter()
+            .filter_map(|entry| match entry {
+                SystemTrayMenuEntry::CustomItem(item) => Some(item),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shows_status_and_checked_toggles() {
+        let menu = tray_menu(&TrayStatus {
+            connection: ConnectionState::Connected,
+            phase: Some(GameflowPhase::Lobby),
+            auto_accept: true,
+            ..Default::default()
+        });
+        let items = items(&menu);
+
+        assert_eq!(items[0].title, "Connected: Lobby");
+        assert!(!items[0].enabled);
+
+        let auto_accept = items.iter().find(|i| i.id_str == AUTO_ACCEPT).unwrap();
+        let auto_open = items.iter().find(|i| i.id_str == AUTO_OPEN).unwrap();
+        assert!(auto_accept.selected);
+        assert!(!auto_open.selected);
+    }
+
+    #[test]
+    fn status_follows_the_connection_state() {
+        let line = |connection| {
+            TrayStatus {
+                connection,
+                phase: Some(GameflowPhase::Lobby),
+                ..Default::default()
+            }
+            .status_line()
+        };
+
+        assert_eq!(line(ConnectionState::Searching), "League client not found");
+        assert_eq!(
+            line(ConnectionState::Authenticating),
+            "Connecting to the League client"
+        );
+        assert_eq!(
+            line(ConnectionState::Degraded),
+            "League client not responding, retrying"
+        );
+        assert_eq!(line(ConnectionState::Connected), "Connected: Lobby");
+    }
+
+    #[test]
+    fn champ_select_actions_only_show_in_champ_select() {
+        let ids = |phase| {
+            let menu = tray_menu(&TrayStatus {
+                connection: ConnectionState::Connected,
+                phase: Some(phase),
+                ..Default::default()
+            });
+            let ids: Vec<String> = items(&menu).iter().map(|i| i.id_str.clone()).collect();
+            ids
+        };
+
+        let lobby = ids(GameflowPhase::Lobby);
+        let champ_select = ids(GameflowPhase::ChampSelect);
+        for id in [DODGE, COPY_TEAM, OPEN_MULTI] {
+            assert!(!lobby.iter().any(|i| i == id));
+            assert!(champ_select.iter().any(|i| i == id));
+        }
+    }
+}
@@ -11,4 +11,13 @@ export interface Participant {
   pid: String;
   puuid: String;
   region: String;
+  played_with?: PlayedWith | null;
+}
+
+export interface PlayedWith {
+  games: number;
+  wins: number;
+  losses: number;
+  winRate: number | null;
+  lastSeen: number;
 }
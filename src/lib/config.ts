@@ -1,11 +1,74 @@
 import { invoke } from "@tauri-apps/api/tauri";
+import type { CommandError } from "./lcu";
+
+export interface AutoPickConfig {
+    pickEnabled: boolean;
+    banEnabled: boolean;
+    lockIn: boolean;
+    picks: Record<string, number[]>;
+    bans: Record<string, number[]>;
+}
+
+export type ReadyCheckAction = "accept" | "decline" | "notify";
+
+export interface AcceptRules {
+    /** By queue id, e.g. 420 ranked solo, 440 flex, 400 normal draft, 450 ARAM, 0 custom. */
+    queues: Record<number, ReadyCheckAction>;
+    defaultAction: ReadyCheckAction;
+    declineWhenAway: boolean;
+    maxConsecutiveAccepts: number;
+}
+
+export interface ReadyCheckDecision {
+    queueId: number;
+    action: ReadyCheckAction;
+    reason: string;
+}
+
+export interface ReadyCheckResult {
+    queueId: number;
+    action: ReadyCheckAction;
+    outcome: "sent" | "failed" | "cancelled";
+    reason: string | null;
+    error: CommandError | null;
+}
+
+export interface NotificationConfig {
+    readyCheck: boolean;
+    champSelect: boolean;
+    pickTurn: boolean;
+    dodge: boolean;
+    sound: boolean;
+}
+
+export async function testNotification() {
+    await invoke("test_notification");
+}
+
+export type TeamFormat = "plain" | "markdown" | "discord" | "link";
+
+export interface TemplateProvider {
+    id: string;
+    name: string;
+    urlTemplate: string;
+    separator: string;
+    tagSeparator: string;
+}
 
 export interface Config {
     autoOpen: boolean;
     autoAccept: boolean;
     acceptDelay: number;
+    acceptRules: AcceptRules;
     multiProvider: string;
-    autoReport: boolean;
+    customProviders: TemplateProvider[];
+    autoCopy: boolean;
+    teamFormat: TeamFormat;
+    reportReview: boolean;
+    recordSessions: boolean;
+    autoPick: AutoPickConfig;
+    dodgeLeadTime: number;
+    notifications: NotificationConfig;
 }
 
 export async function updateConfig(config: Config) {
@@ -13,3 +76,34 @@ export async function updateConfig(config: Config) {
         newCfg: config
     });
 }
+
+export interface ProfileSummary {
+    name: string;
+    puuid: string | null;
+    active: boolean;
+}
+
+export async function listProfiles(): Promise<ProfileSummary[]> {
+    return await invoke("list_profiles");
+}
+
+export async function switchProfile(name: string): Promise<Config> {
+    return await invoke("switch_profile", { name });
+}
+
+export async function saveProfile(name: string, bindAccount: boolean): Promise<ProfileSummary[]> {
+    return await invoke("save_profile", { name, bindAccount });
+}
+
+export interface ProviderInfo {
+    id: string;
+    name: string;
+}
+
+export async function listProviders(): Promise<ProviderInfo[]> {
+    return await invoke("list_providers");
+}
+
+export async function copyTeam(format?: TeamFormat): Promise<string> {
+    return await invoke("copy_team", { format });
+}
@@ -1,6 +1,114 @@
+import { invoke } from "@tauri-apps/api/tauri";
 
-export interface EndGame {
+export type ReportCategory =
+  | "NEGATIVE_ATTITUDE"
+  | "VERBAL_ABUSE"
+  | "LEAVING_AFK"
+  | "ASSISTING_ENEMY_TEAM"
+  | "HATE_SPEECH"
+  | "THIRD_PARTY_TOOLS"
+  | "INAPPROPRIATE_NAME";
+
+export const REPORT_CATEGORIES: { value: ReportCategory; label: string }[] = [
+  { value: "NEGATIVE_ATTITUDE", label: "Negative attitude" },
+  { value: "VERBAL_ABUSE", label: "Verbal abuse" },
+  { value: "LEAVING_AFK", label: "Leaving / AFK" },
+  { value: "ASSISTING_ENEMY_TEAM", label: "Assisting enemy team" },
+  { value: "HATE_SPEECH", label: "Hate speech" },
+  { value: "THIRD_PARTY_TOOLS", label: "Third party tools" },
+  { value: "INAPPROPRIATE_NAME", label: "Inappropriate name" },
+];
+
+export interface ReportCandidate {
   summonerId: number;
   puuid: string;
+  gameName: string;
+  tagLine: string;
+  championId: number;
+  teammate: boolean;
+  friend: boolean;
+  reported: boolean;
+}
+
+export interface ReportQueue {
   gameId: number;
+  players: ReportCandidate[];
+}
+
+export async function getReportQueue(): Promise<ReportQueue | null> {
+  return await invoke("get_report_queue");
+}
+
+export async function submitReport(
+  gameId: number,
+  puuid: string,
+  categories: ReportCategory[],
+  comment = "",
+): Promise<ReportQueue> {
+  return await invoke("submit_report", {
+    report: { gameId, puuid, categories, comment },
+  });
+}
+
+export interface PlayerStats {
+  kills: number;
+  deaths: number;
+  assists: number;
+  goldEarned: number;
+  damageToChampions: number;
+  damageTaken: number;
+  minionsKilled: number;
+  neutralMinionsKilled: number;
+  visionScore: number;
+}
+
+export interface EndOfGamePlayer {
+  summonerId: number;
+  puuid: string;
+  riotIdGameName: string;
+  riotIdTagLine: string;
+  championId: number;
+  championName: string;
+  teamId: number;
+  items: number[];
+  stats: PlayerStats;
+}
+
+export interface EndOfGameTeam {
+  teamId: number;
+  isPlayerTeam: boolean;
+  isWinningTeam: boolean;
+  players: EndOfGamePlayer[];
+}
+
+export interface LpChange {
+  queueType: string;
+  leaguePointsDelta: number;
+  leaguePoints: number;
+  tier: string;
+  division: string;
+}
+
+export interface MasteryChange {
+  championId: number;
+  championLevel: number;
+  pointsBeforeGame: number;
+  pointsGained: number;
+  levelUp: boolean;
+}
+
+export interface EndOfGameStats {
+  gameId: number;
+  gameLength: number;
+  gameMode: string;
+  queueType: string;
+  queueId: number | null;
+  localPlayer: EndOfGamePlayer;
+  teams: EndOfGameTeam[];
+  lpChange: LpChange | null;
+  masteryChange: MasteryChange | null;
+}
+
+export async function getLastGameStats(): Promise<EndOfGameStats | null> {
+  return await invoke("get_last_game_stats");
 }
@@ -0,0 +1,43 @@
+export type ConnectionState =
+  | "Searching"
+  | "Authenticating"
+  | "Connected"
+  | "Degraded"
+  | "Disconnected";
+
+export interface CommandError {
+  code:
+    | "NOT_CONNECTED"
+    | "NOT_IN_CHAMP_SELECT"
+    | "LCU_ERROR"
+    | "INVALID_RESPONSE"
+    | "IO_ERROR"
+    | "UNKNOWN_PROFILE"
+    | "UNKNOWN_PROVIDER"
+    | "CLIPBOARD_ERROR"
+    | "DATABASE_ERROR"
+    | "INVALID_REPORT";
+  message: string;
+  status: number | null;
+}
+
+export interface QueuePenalty {
+  errorType: string;
+  penalizedSummonerId: number;
+  penaltyTimeRemaining: number;
+}
+
+export interface PenaltyTier {
+  minDodges: number;
+  lockoutMinutes: number;
+}
+
+export interface PenaltyStatus {
+  dodgesInWindow: number;
+  currentTier: PenaltyTier | null;
+  nextDodgeTier: PenaltyTier;
+  dodgesUntilNextTier: number | null;
+  windowResetsAt: number | null;
+  penalties: QueuePenalty[];
+  penaltyExpiresAt: number | null;
+}
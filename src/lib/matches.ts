@@ -0,0 +1,35 @@
+import { invoke } from "@tauri-apps/api/tauri";
+
+export interface TeammateRecord {
+    puuid: string;
+    gameName: string;
+    gameTag: string;
+}
+
+export interface GameRecord {
+    gameId: number;
+    queueId: number | null;
+    championId: number | null;
+    startedAt: number;
+    won: boolean | null;
+    dodged: boolean;
+    teammates: TeammateRecord[];
+}
+
+export interface TeammateEncounter {
+    puuid: string;
+    gameName: string;
+    gameTag: string;
+    games: number;
+    wins: number;
+    losses: number;
+    lastSeen: number;
+}
+
+export async function getRecentGames(limit?: number): Promise<GameRecord[]> {
+    return await invoke("get_recent_games", { limit });
+}
+
+export async function getTeammateEncounters(limit?: number): Promise<TeammateEncounter[]> {
+    return await invoke("get_teammate_encounters", { limit });
+}